@@ -0,0 +1,36 @@
+//! Checks a folder of blueprints/levels against an exported registry, for use in content pipelines / CI
+//! usage: `blenvy-lint <assets_folder> <registry.json>`
+use std::{fs, path::PathBuf, process::ExitCode};
+
+use blenvy::lint_assets;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().collect();
+    let [_, assets_path, registry_path] = args.as_slice() else {
+        eprintln!("usage: blenvy-lint <assets_folder> <registry.json>");
+        return ExitCode::from(2);
+    };
+
+    let registry = match fs::read_to_string(registry_path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+    {
+        Ok(registry) => registry,
+        Err(error) => {
+            eprintln!("could not read registry file {registry_path}: {error}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let issues = lint_assets(&PathBuf::from(assets_path), &registry);
+    for issue in issues.iter() {
+        println!("{issue}");
+    }
+    if issues.is_empty() {
+        println!("no issues found");
+        ExitCode::SUCCESS
+    } else {
+        println!("{} issue(s) found", issues.len());
+        ExitCode::FAILURE
+    }
+}
@@ -0,0 +1,48 @@
+//! Compares two registry exports & prints a (json) report of the added, removed, renamed & changed types
+//! usage: `blenvy-registry-diff <old_registry.json> <new_registry.json> [assets_folder]`
+//! with an assets folder, the report also lists the gltf files using the removed/renamed/changed types
+use std::{fs, path::PathBuf, process::ExitCode};
+
+use blenvy::{assets_using_types, diff_registries};
+use serde_json::{json, Value};
+
+fn read_registry(path: &str) -> Result<Value, String> {
+    fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+        .map_err(|error| format!("could not read registry file {path}: {error}"))
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().collect();
+    let (old_path, new_path, assets_path) = match args.as_slice() {
+        [_, old_path, new_path] => (old_path, new_path, None),
+        [_, old_path, new_path, assets_path] => (old_path, new_path, Some(assets_path)),
+        _ => {
+            eprintln!("usage: blenvy-registry-diff <old_registry.json> <new_registry.json> [assets_folder]");
+            return ExitCode::from(2);
+        }
+    };
+
+    let (old, new) = match (read_registry(old_path), read_registry(new_path)) {
+        (Ok(old), Ok(new)) => (old, new),
+        (Err(error), _) | (_, Err(error)) => {
+            eprintln!("{error}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let diff = diff_registries(&old, &new);
+    let mut report = json!(diff);
+    if let Some(assets_path) = assets_path {
+        let affected = assets_using_types(&PathBuf::from(assets_path), &old, &diff.affected_types());
+        report["affected_files"] = json!(affected);
+    }
+    println!("{}", serde_json::to_string_pretty(&report).expect("valid json"));
+
+    if diff.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
@@ -1,72 +1,549 @@
-use bevy::{math::Vec3A, prelude::*, render::primitives::Aabb};
+use std::hash::{Hash, Hasher};
 
-use crate::{BlenvyConfig, BlueprintReadyForFinalizing, BlueprintReadyForPostProcess};
+use bevy::{
+    gltf::{GltfMesh, GltfNode},
+    ecs::query::QueryFilter,
+    math::{Affine3A, BVec3A, Vec3A},
+    prelude::*,
+    render::{mesh::skinning::SkinnedMesh, mesh::VertexAttributeValues, primitives::Aabb},
+    utils::{AHasher, HashMap},
+};
+
+use crate::{
+    BlenvyComponent, BlueprintInfo, BlueprintInstanceReady, BlueprintReadyForFinalizing,
+    BlueprintReadyForPostProcess,
+};
+
+/// Cache of the compound bounds of blueprints, by blueprint path & content hash of the blueprint's gltf file,
+/// so that all instances of a blueprint share the same (untransformed) bounds, no matter how they are named
+#[derive(Resource, Default, Debug)]
+pub struct BlueprintAabbCache {
+    aabbs: HashMap<(String, u64), BlueprintBounds>,
+    content_hashes: HashMap<AssetId<Gltf>, u64>,
+}
+
+impl BlueprintAabbCache {
+    pub fn get(&self, blueprint_path: &str, content_hash: u64) -> Option<&BlueprintBounds> {
+        self.aabbs.get(&(blueprint_path.to_string(), content_hash))
+    }
+
+    /// removes the cached aabbs of a blueprint, they get recomputed the next time an instance of it is spawned
+    pub fn invalidate(&mut self, blueprint_path: &str) {
+        self.aabbs.retain(|(path, _), _| path != blueprint_path);
+    }
+
+    /// content hash of a blueprint's gltf file, only cached once it could be computed (ie once the gltf file is loaded)
+    fn content_hash(&mut self, gltf_id: AssetId<Gltf>, compute: impl FnOnce() -> Option<u64>) -> Option<u64> {
+        if let Some(content_hash) = self.content_hashes.get(&gltf_id) {
+            return Some(*content_hash);
+        }
+        let content_hash = compute()?;
+        self.content_hashes.insert(gltf_id, content_hash);
+        Some(content_hash)
+    }
+
+    pub fn clear(&mut self) {
+        self.aabbs.clear();
+        self.content_hashes.clear();
+    }
+}
+
+/// Add this to a blueprint instance (or in Blender, to the blueprint) to recompute its aabb every frame instead of using the cached one,
+/// ie for deformed / skinned meshes: the bind pose bounds of skinned meshes get extended with the current positions of their joints
+#[derive(Component, Reflect, Default, Debug, BlenvyComponent)]
+#[reflect(Component)]
+pub struct RecomputeAabb;
+
+/// Add this to a blueprint (in Blender) or an instance to also get its [`BlueprintBoundingSphere`] and / or [`BlueprintObb`],
+/// computed along with its `Aabb`
+#[derive(Component, Reflect, Default, Debug, Clone, Copy, BlenvyComponent)]
+#[reflect(Component)]
+pub struct BoundingVolumes {
+    pub sphere: bool,
+    pub oriented_box: bool,
+}
+
+/// Bounding sphere of a blueprint instance, in the instance's local space
+#[derive(Component, Reflect, Default, Debug, Clone, Copy, PartialEq)]
+#[reflect(Component)]
+pub struct BlueprintBoundingSphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+/// Oriented bounding box of a blueprint instance, in the instance's local space: the smallest of the boxes aligned with the instance
+/// or with one of its meshes
+#[derive(Component, Reflect, Default, Debug, Clone, Copy, PartialEq)]
+#[reflect(Component)]
+pub struct BlueprintObb {
+    pub center: Vec3,
+    pub half_extents: Vec3,
+    pub rotation: Quat,
+}
+
+/// The compound bounding volumes of a blueprint, in the local space of its root entity
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlueprintBounds {
+    pub aabb: Aabb,
+    pub sphere: BlueprintBoundingSphere,
+    pub obb: BlueprintObb,
+}
+
+impl BlueprintBounds {
+    /// bounds of a set of points, the oriented box is the smallest one aligned with one of the `orientations`
+    /// (or with the axes), returns `None` if there are no points
+    pub fn from_points(points: &[Vec3A], orientations: &[Quat]) -> Option<Self> {
+        let (min, max) = points
+            .iter()
+            .fold(None, |bounds: Option<(Vec3A, Vec3A)>, point| match bounds {
+                Some((min, max)) => Some((min.min(*point), max.max(*point))),
+                None => Some((*point, *point)),
+            })?;
+        let aabb = Aabb::from_min_max(Vec3::from(min), Vec3::from(max));
+
+        let radius = points
+            .iter()
+            .map(|point| point.distance_squared(aabb.center))
+            .fold(0.0, f32::max)
+            .sqrt();
+        let sphere = BlueprintBoundingSphere {
+            center: Vec3::from(aabb.center),
+            radius,
+        };
+
+        let mut obb = BlueprintObb {
+            center: Vec3::from(aabb.center),
+            half_extents: Vec3::from(aabb.half_extents),
+            rotation: Quat::IDENTITY,
+        };
+        for rotation in orientations {
+            let inverse = rotation.inverse();
+            let (local_min, local_max) = points.iter().fold(
+                (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+                |(min, max), point| {
+                    let local = inverse * Vec3::from(*point);
+                    (min.min(local), max.max(local))
+                },
+            );
+            let half_extents = (local_max - local_min) / 2.0;
+            if half_extents.x * half_extents.y * half_extents.z
+                < obb.half_extents.x * obb.half_extents.y * obb.half_extents.z
+            {
+                obb = BlueprintObb {
+                    center: *rotation * ((local_min + local_max) / 2.0),
+                    half_extents,
+                    rotation: *rotation,
+                };
+            }
+        }
+
+        Some(Self { aabb, sphere, obb })
+    }
+
+    fn insert_into(&self, entity: &mut EntityCommands, bounding_volumes: Option<&BoundingVolumes>) {
+        entity.insert(self.aabb);
+        if let Some(bounding_volumes) = bounding_volumes {
+            if bounding_volumes.sphere {
+                entity.insert(self.sphere);
+            }
+            if bounding_volumes.oriented_box {
+                entity.insert(self.obb);
+            }
+        }
+    }
+}
+
+/// hash of the node transforms & vertex positions of a gltf file
+fn gltf_content_hash(
+    gltf: &Gltf,
+    gltf_nodes: &Assets<GltfNode>,
+    gltf_meshes: &Assets<GltfMesh>,
+    meshes: &Assets<Mesh>,
+) -> u64 {
+    let mut hasher = AHasher::default();
+    for node in gltf.nodes.iter().filter_map(|node| gltf_nodes.get(node)) {
+        node.name.hash(&mut hasher);
+        node.transform
+            .compute_matrix()
+            .to_cols_array()
+            .map(f32::to_bits)
+            .hash(&mut hasher);
+    }
+    let primitives = gltf
+        .meshes
+        .iter()
+        .filter_map(|gltf_mesh| gltf_meshes.get(gltf_mesh))
+        .flat_map(|gltf_mesh| gltf_mesh.primitives.iter());
+    for primitive in primitives {
+        if let Some(VertexAttributeValues::Float32x3(positions)) = meshes
+            .get(&primitive.mesh)
+            .and_then(|mesh| mesh.attribute(Mesh::ATTRIBUTE_POSITION))
+        {
+            for position in positions {
+                position.map(f32::to_bits).hash(&mut hasher);
+            }
+        }
+    }
+    hasher.finish()
+}
 
 /// helper system that computes the compound aabbs of the scenes/blueprints
+#[allow(clippy::too_many_arguments)]
 pub fn compute_scene_aabbs(
-    root_entities: Query<(Entity, &Name), (With<BlueprintReadyForPostProcess>, Without<Aabb>)>,
+    root_entities: Query<
+        (Entity, &Name, &BlueprintInfo, Has<RecomputeAabb>, Option<&BoundingVolumes>),
+        (With<BlueprintReadyForPostProcess>, Without<Aabb>),
+    >,
     other_entities: Query<Entity, (With<BlueprintReadyForPostProcess>, With<Aabb>)>,
     children: Query<&Children>,
+    transforms: Query<&Transform>,
     existing_aabbs: Query<&Aabb>,
+    assets_gltf: Res<Assets<Gltf>>,
+    gltf_nodes: Res<Assets<GltfNode>>,
+    gltf_meshes: Res<Assets<GltfMesh>>,
+    meshes: Res<Assets<Mesh>>,
+    asset_server: Res<AssetServer>,
 
-    mut blenvy_config: ResMut<BlenvyConfig>,
+    mut aabb_cache: ResMut<BlueprintAabbCache>,
     mut commands: Commands,
 ) {
     // compute compound aabb
-    for (root_entity, name) in root_entities.iter() {
-        // info!("generating aabb for {:?}", name);
-
-        // only recompute aabb if it has not already been done before
-        if blenvy_config.aabb_cache.contains_key(&name.to_string()) {
-            let aabb = blenvy_config
-                .aabb_cache
-                .get(&name.to_string())
-                .expect("we should have the aabb available");
-            commands
-                .entity(root_entity)
-                .insert(*aabb)
-                .insert(BlueprintReadyForFinalizing);
-        } else {
-            let aabb = compute_descendant_aabb(root_entity, &children, &existing_aabbs);
-            blenvy_config.aabb_cache.insert(name.to_string(), aabb);
-            info!("Step 7: generating aabb for {:?}", name);
-            commands
-                .entity(root_entity)
-                .insert(aabb)
-                .insert(BlueprintReadyForFinalizing);
+    for (root_entity, name, blueprint_info, recompute, bounding_volumes) in root_entities.iter() {
+        let gltf_id = asset_server.load::<Gltf>(&blueprint_info.path).id();
+        let content_hash = aabb_cache.content_hash(gltf_id, || {
+            assets_gltf
+                .get(gltf_id)
+                .map(|gltf| gltf_content_hash(gltf, &gltf_nodes, &gltf_meshes, &meshes))
+        });
+
+        // only recompute aabb if it has not already been done before (dynamic aabbs are not cached,
+        // neither are the aabbs of blueprints whose gltf file is not loaded, as their content is unknown)
+        let cached_bounds = content_hash
+            .filter(|_| !recompute)
+            .and_then(|content_hash| aabb_cache.get(&blueprint_info.path, content_hash));
+        let bounds = match cached_bounds {
+            Some(bounds) => Some(*bounds),
+            None => {
+                info!("Step 7: generating aabb for {:?}", name);
+                let bounds = compute_descendant_bounds(root_entity, &children, &transforms, &existing_aabbs);
+                if let (Some(bounds), Some(content_hash), false) = (bounds, content_hash, recompute) {
+                    aabb_cache
+                        .aabbs
+                        .insert((blueprint_info.path.clone(), content_hash), bounds);
+                }
+                bounds
+            }
+        };
+        let mut root_commands = commands.entity(root_entity);
+        // blueprints without any meshes have no bounds
+        if let Some(bounds) = bounds {
+            bounds.insert_into(&mut root_commands, bounding_volumes);
         }
+        root_commands.insert(BlueprintReadyForFinalizing);
     }
     for entity in other_entities.iter() {
         commands.entity(entity).insert(BlueprintReadyForFinalizing);
     }
 }
 
-pub fn compute_descendant_aabb(
+/// invalidates the cached aabbs of modified blueprints
+pub(crate) fn invalidate_blueprint_aabbs(
+    mut gltf_events: EventReader<AssetEvent<Gltf>>,
+    mut aabb_cache: ResMut<BlueprintAabbCache>,
+    asset_server: Res<AssetServer>,
+) {
+    for event in gltf_events.read() {
+        if let AssetEvent::Modified { id } | AssetEvent::Removed { id } = event {
+            aabb_cache.content_hashes.remove(id);
+            if let Some(asset_path) = asset_server.get_path(*id) {
+                debug!("Invalidating aabbs of {}", asset_path);
+                aabb_cache.invalidate(&asset_path.to_string());
+            }
+        }
+    }
+}
+
+/// recomputes the aabbs of blueprint instances with [`RecomputeAabb`]
+pub(crate) fn recompute_dynamic_aabbs(
+    instances: Query<
+        (Entity, &GlobalTransform, Option<&BoundingVolumes>),
+        (With<RecomputeAabb>, With<BlueprintInstanceReady>),
+    >,
+    children: Query<&Children>,
+    transforms: Query<&Transform>,
+    existing_aabbs: Query<&Aabb, Without<RecomputeAabb>>,
+    skinned_meshes: Query<&SkinnedMesh>,
+    global_transforms: Query<&GlobalTransform>,
+    mut commands: Commands,
+) {
+    for (entity, global_transform, bounding_volumes) in instances.iter() {
+        let mut points = vec![];
+        let mut orientations = vec![];
+        collect_descendant_points(
+            entity,
+            Affine3A::IDENTITY,
+            &children,
+            &transforms,
+            &existing_aabbs,
+            &mut points,
+            &mut orientations,
+        );
+        // the bounds of skinned meshes are those of their bind pose: extend them with the current positions of the joints
+        let to_local = global_transform.affine().inverse();
+        for descendant in children.iter_descendants(entity) {
+            let Ok(skinned_mesh) = skinned_meshes.get(descendant) else {
+                continue;
+            };
+            for joint in global_transforms.iter_many(&skinned_mesh.joints) {
+                points.push(to_local.transform_point3a(joint.translation_vec3a()));
+            }
+        }
+        if let Some(bounds) = BlueprintBounds::from_points(&points, &orientations) {
+            bounds.insert_into(&mut commands.entity(entity), bounding_volumes);
+        }
+    }
+}
+
+/// the compound bounds of the descendants of an entity, in its local space: the `Aabb`s of the descendants are transformed by their
+/// `Transform` relative to the entity (descendants with an `Aabb` are not traversed further), returns `None` if none of them has an `Aabb`.
+/// Uses `Transform`s rather than `GlobalTransform`s, as the latter are not propagated yet for freshly spawned blueprints
+pub fn compute_descendant_bounds<F: QueryFilter>(
+    root_entity: Entity,
+    children: &Query<&Children>,
+    transforms: &Query<&Transform>,
+    existing_aabbs: &Query<&Aabb, F>,
+) -> Option<BlueprintBounds> {
+    let mut points = vec![];
+    let mut orientations = vec![];
+    collect_descendant_points(
+        root_entity,
+        Affine3A::IDENTITY,
+        children,
+        transforms,
+        existing_aabbs,
+        &mut points,
+        &mut orientations,
+    );
+    BlueprintBounds::from_points(&points, &orientations)
+}
+
+/// the compound `Aabb` of the descendants of an entity, in its local space, see [`compute_descendant_bounds`]
+pub fn compute_descendant_aabb<F: QueryFilter>(
     root_entity: Entity,
     children: &Query<&Children>,
-    existing_aabbs: &Query<&Aabb>,
-) -> Aabb {
-    if let Ok(children_list) = children.get(root_entity) {
-        let mut chilren_aabbs: Vec<Aabb> = vec![];
-        for child in children_list.iter() {
-            if let Ok(aabb) = existing_aabbs.get(*child) {
-                chilren_aabbs.push(*aabb);
-            } else {
-                let aabb = compute_descendant_aabb(*child, children, existing_aabbs);
-                chilren_aabbs.push(aabb);
+    transforms: &Query<&Transform>,
+    existing_aabbs: &Query<&Aabb, F>,
+) -> Option<Aabb> {
+    compute_descendant_bounds(root_entity, children, transforms, existing_aabbs).map(|bounds| bounds.aabb)
+}
+
+/// collects the corners of the `Aabb`s of the descendants of `entity` (whose transform relative to the root is `to_root`),
+/// and the orientations of the descendants with an `Aabb`
+fn collect_descendant_points<F: QueryFilter>(
+    entity: Entity,
+    to_root: Affine3A,
+    children: &Query<&Children>,
+    transforms: &Query<&Transform>,
+    existing_aabbs: &Query<&Aabb, F>,
+    points: &mut Vec<Vec3A>,
+    orientations: &mut Vec<Quat>,
+) {
+    let Ok(children_list) = children.get(entity) else {
+        return;
+    };
+    for child in children_list.iter() {
+        let child_to_root = transforms
+            .get(*child)
+            .map_or(to_root, |transform| to_root * transform.compute_affine());
+        if let Ok(aabb) = existing_aabbs.get(*child) {
+            let (min, max) = (aabb.min(), aabb.max());
+            for corner in 0..8 {
+                let corner = Vec3A::select(
+                    BVec3A::new(corner & 1 != 0, corner & 2 != 0, corner & 4 != 0),
+                    max,
+                    min,
+                );
+                points.push(child_to_root.transform_point3a(corner));
+            }
+            let (_, rotation, _) = child_to_root.to_scale_rotation_translation();
+            if !orientations
+                .iter()
+                .any(|orientation: &Quat| orientation.abs_diff_eq(rotation, 1e-4))
+            {
+                orientations.push(rotation);
             }
+        } else {
+            collect_descendant_points(
+                *child,
+                child_to_root,
+                children,
+                transforms,
+                existing_aabbs,
+                points,
+                orientations,
+            );
         }
+    }
+}
 
-        let mut min = Vec3A::splat(f32::MAX);
-        let mut max = Vec3A::splat(f32::MIN);
-        for aabb in chilren_aabbs.iter() {
-            min = min.min(aabb.min());
-            max = max.max(aabb.max());
-        }
-        let aabb = Aabb::from_min_max(Vec3::from(min), Vec3::from(max));
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!(a.abs_diff_eq(b, 1e-4), "{a} != {b}");
+    }
+
+    fn box_corners(center: Vec3, half_extents: Vec3, rotation: Quat) -> Vec<Vec3A> {
+        (0..8)
+            .map(|corner| {
+                let sign = Vec3::new(
+                    if corner & 1 != 0 { 1.0 } else { -1.0 },
+                    if corner & 2 != 0 { 1.0 } else { -1.0 },
+                    if corner & 4 != 0 { 1.0 } else { -1.0 },
+                );
+                Vec3A::from(center + rotation * (sign * half_extents))
+            })
+            .collect()
+    }
+
+    fn descendant_bounds(world: &mut World, root: Entity) -> Option<BlueprintBounds> {
+        world
+            .run_system_once(move |children: Query<&Children>, transforms: Query<&Transform>, aabbs: Query<&Aabb>| {
+                compute_descendant_bounds(root, &children, &transforms, &aabbs)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn bounds_of_no_points() {
+        assert_eq!(BlueprintBounds::from_points(&[], &[Quat::IDENTITY]), None);
+    }
+
+    #[test]
+    fn content_hashes_of_unloaded_gltfs_are_not_cached() {
+        let mut cache = BlueprintAabbCache::default();
+        let gltf_id = AssetId::<Gltf>::default();
+        assert_eq!(cache.content_hash(gltf_id, || None), None);
+        assert!(cache.content_hashes.is_empty());
 
-        return aabb;
+        assert_eq!(cache.content_hash(gltf_id, || Some(42)), Some(42));
+        // once loaded, the hash is not recomputed
+        assert_eq!(cache.content_hash(gltf_id, || panic!("recomputed")), Some(42));
     }
 
-    Aabb::default()
+    #[test]
+    fn bounds_of_axis_aligned_points() {
+        let points = box_corners(Vec3::new(1.0, 2.0, 3.0), Vec3::new(1.0, 2.0, 2.0), Quat::IDENTITY);
+        let bounds = BlueprintBounds::from_points(&points, &[]).unwrap();
+        assert_close(bounds.aabb.center.into(), Vec3::new(1.0, 2.0, 3.0));
+        assert_close(bounds.aabb.half_extents.into(), Vec3::new(1.0, 2.0, 2.0));
+        // the corners are the furthest points from the center
+        assert_close(bounds.sphere.center, Vec3::new(1.0, 2.0, 3.0));
+        assert!((bounds.sphere.radius - 3.0).abs() < 1e-5);
+        assert_eq!(bounds.obb.rotation, Quat::IDENTITY);
+        assert_close(bounds.obb.half_extents, Vec3::new(1.0, 2.0, 2.0));
+    }
+
+    #[test]
+    fn bounding_sphere_radius() {
+        let points = [Vec3A::ZERO, Vec3A::new(2.0, 0.0, 0.0), Vec3A::new(0.0, 2.0, 0.0)];
+        let bounds = BlueprintBounds::from_points(&points, &[]).unwrap();
+        assert_close(bounds.sphere.center, Vec3::new(1.0, 1.0, 0.0));
+        assert!((bounds.sphere.radius - 2.0_f32.sqrt()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn oriented_box_axes() {
+        let rotation = Quat::from_rotation_z(FRAC_PI_4);
+        let center = Vec3::new(5.0, 0.0, -1.0);
+        let points = box_corners(center, Vec3::new(2.0, 0.5, 0.5), rotation);
+        // the axes & an unrelated orientation are tried too, but give larger boxes
+        let bounds =
+            BlueprintBounds::from_points(&points, &[Quat::IDENTITY, rotation, Quat::from_rotation_x(0.3)]).unwrap();
+        assert!(bounds.obb.rotation.abs_diff_eq(rotation, 1e-5));
+        assert_close(bounds.obb.center, center);
+        assert_close(bounds.obb.half_extents, Vec3::new(2.0, 0.5, 0.5));
+        // the aabb encloses the rotated box
+        let diagonal = 2.5 / 2.0_f32.sqrt();
+        assert_close(bounds.aabb.half_extents.into(), Vec3::new(diagonal, diagonal, 0.5));
+        assert!((bounds.sphere.radius - 4.5_f32.sqrt()).abs() < 1e-4);
+
+        // without the matching orientation, the box stays axis aligned
+        let bounds = BlueprintBounds::from_points(&points, &[Quat::from_rotation_x(0.3)]).unwrap();
+        assert_eq!(bounds.obb.rotation, Quat::IDENTITY);
+        assert_close(bounds.obb.half_extents, Vec3::from(bounds.aabb.half_extents));
+    }
+
+    #[test]
+    fn rotated_child_corners() {
+        let mut world = World::new();
+        let rotation = Quat::from_rotation_y(FRAC_PI_2);
+        let root = world
+            .spawn(Transform::from_xyz(100.0, 0.0, 0.0))
+            .with_children(|parent| {
+                parent.spawn((
+                    Transform::from_xyz(5.0, 0.0, 0.0).with_rotation(rotation),
+                    Aabb::from_min_max(Vec3::new(-2.0, -1.0, -0.5), Vec3::new(2.0, 1.0, 0.5)),
+                ));
+            })
+            .id();
+        let bounds = descendant_bounds(&mut world, root).unwrap();
+        // in the space of the root, whose own transform is ignored: x & z swapped by the rotation
+        assert_close(bounds.aabb.center.into(), Vec3::new(5.0, 0.0, 0.0));
+        assert_close(bounds.aabb.half_extents.into(), Vec3::new(0.5, 1.0, 2.0));
+        // the orientation of the child is used for the oriented box
+        assert!(bounds.obb.rotation.abs_diff_eq(rotation, 1e-5));
+        assert_close(bounds.obb.half_extents, Vec3::new(2.0, 1.0, 0.5));
+    }
+
+    #[test]
+    fn nested_descendant_corners() {
+        let mut world = World::new();
+        let root = world
+            .spawn(Transform::IDENTITY)
+            .with_children(|parent| {
+                // no aabb: traversed, its transform is applied to its children
+                parent
+                    .spawn(Transform::from_xyz(0.0, 10.0, 0.0).with_scale(Vec3::splat(2.0)))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            Transform::from_xyz(1.0, 0.0, 0.0),
+                            Aabb::from_min_max(Vec3::splat(-1.0), Vec3::splat(1.0)),
+                        ));
+                    });
+                // with an aabb: its descendants are not traversed
+                parent
+                    .spawn((
+                        Transform::from_xyz(-3.0, 0.0, 0.0),
+                        Aabb::from_min_max(Vec3::splat(-1.0), Vec3::splat(1.0)),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            Transform::from_xyz(-100.0, 0.0, 0.0),
+                            Aabb::from_min_max(Vec3::splat(-1.0), Vec3::splat(1.0)),
+                        ));
+                    });
+            })
+            .id();
+        let bounds = descendant_bounds(&mut world, root).unwrap();
+        assert_close(bounds.aabb.min().into(), Vec3::new(-4.0, -1.0, -2.0));
+        assert_close(bounds.aabb.max().into(), Vec3::new(4.0, 12.0, 2.0));
+    }
+
+    #[test]
+    fn no_descendant_aabbs() {
+        let mut world = World::new();
+        let root = world
+            .spawn(Transform::IDENTITY)
+            .with_children(|parent| {
+                parent.spawn(Transform::from_xyz(1.0, 0.0, 0.0));
+            })
+            .id();
+        assert_eq!(descendant_bounds(&mut world, root), None);
+    }
 }
@@ -0,0 +1,222 @@
+use std::{fmt, time::Duration};
+
+use bevy::{
+    animation::ActiveAnimation, ecs::system::SystemParam, prelude::*, utils::HashMap,
+};
+
+use crate::{
+    BlueprintAnimationPlayerLink, BlueprintAnimations, InstanceAnimationPlayerLink,
+    InstanceAnimations,
+};
+
+/// Which animations of an entity to control: an entity can have both
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnimationSource {
+    /// the animations of the blueprint ([`BlueprintAnimations`]), ie those of a character
+    Blueprint,
+    /// the per instance / scene level animations ([`InstanceAnimations`]), ie those of a door in a level
+    Instance,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnimationControlError {
+    /// the entity has no animations of that source
+    NoAnimations(Entity, AnimationSource),
+    /// the entity linked as animation player has no `AnimationPlayer`
+    NoAnimationPlayer(Entity),
+    UnknownAnimation(String),
+}
+
+impl fmt::Display for AnimationControlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoAnimations(entity, source) => {
+                write!(f, "entity {entity:?} has no {source:?} animations")
+            }
+            Self::NoAnimationPlayer(entity) => write!(f, "entity {entity:?} has no AnimationPlayer"),
+            Self::UnknownAnimation(name) => write!(f, "unknown animation '{name}'"),
+        }
+    }
+}
+
+impl std::error::Error for AnimationControlError {}
+
+/// High level animation playback for blueprint instances: resolves the animation player links & animation names
+/// for both blueprint & instance animations, ie
+/// ```rust ignore
+/// fn play_walk(mut animations: BlueprintAnimationControl, foxes: Query<Entity, With<Fox>>) {
+///     for fox in foxes.iter() {
+///         if let Ok(walk) = animations.crossfade(fox, "Walk", Duration::from_millis(300)) {
+///             walk.repeat();
+///         }
+///     }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct BlueprintAnimationControl<'w, 's> {
+    blueprint_animations: Query<'w, 's, (&'static BlueprintAnimationPlayerLink, &'static BlueprintAnimations)>,
+    instance_animations: Query<'w, 's, (&'static InstanceAnimationPlayerLink, &'static InstanceAnimations)>,
+    players: Query<'w, 's, (&'static mut AnimationPlayer, Option<&'static mut AnimationTransitions>)>,
+}
+
+impl BlueprintAnimationControl<'_, '_> {
+    /// the animations of an entity: its blueprint animations if it has any, its instance animations otherwise
+    pub fn animations(&mut self, entity: Entity) -> Result<AnimationsMut<'_>, AnimationControlError> {
+        let source = if self.blueprint_animations.contains(entity) {
+            AnimationSource::Blueprint
+        } else {
+            AnimationSource::Instance
+        };
+        self.animations_from(entity, source)
+    }
+
+    pub fn animations_from(
+        &mut self,
+        entity: Entity,
+        source: AnimationSource,
+    ) -> Result<AnimationsMut<'_>, AnimationControlError> {
+        let (player_entity, named_indices) = match source {
+            AnimationSource::Blueprint => self
+                .blueprint_animations
+                .get(entity)
+                .map(|(link, animations)| (link.0, &animations.named_indices)),
+            AnimationSource::Instance => self
+                .instance_animations
+                .get(entity)
+                .map(|(link, animations)| (link.0, &animations.named_indices)),
+        }
+        .map_err(|_| AnimationControlError::NoAnimations(entity, source))?;
+        let (player, transitions) = self
+            .players
+            .get_mut(player_entity)
+            .map_err(|_| AnimationControlError::NoAnimationPlayer(player_entity))?;
+        Ok(AnimationsMut {
+            player: player.into_inner(),
+            transitions: transitions.map(Mut::into_inner),
+            named_indices,
+        })
+    }
+
+    /// plays the animation right away, stopping the others, see [`AnimationsMut::play`]
+    pub fn play(&mut self, entity: Entity, name: &str) -> Result<&mut ActiveAnimation, AnimationControlError> {
+        self.animations(entity)?.play(name)
+    }
+
+    /// fades out the current animation while fading in this one, see [`AnimationsMut::crossfade`]
+    pub fn crossfade(
+        &mut self,
+        entity: Entity,
+        name: &str,
+        duration: Duration,
+    ) -> Result<&mut ActiveAnimation, AnimationControlError> {
+        self.animations(entity)?.crossfade(name, duration)
+    }
+
+    pub fn stop(&mut self, entity: Entity, name: &str) -> Result<(), AnimationControlError> {
+        self.animations(entity)?.stop(name)
+    }
+
+    pub fn stop_all(&mut self, entity: Entity) -> Result<(), AnimationControlError> {
+        self.animations(entity)?.stop_all();
+        Ok(())
+    }
+
+    pub fn set_speed(&mut self, entity: Entity, name: &str, speed: f32) -> Result<(), AnimationControlError> {
+        self.animations(entity)?.set_speed(name, speed)
+    }
+
+    pub fn seek(&mut self, entity: Entity, name: &str, seconds: f32) -> Result<(), AnimationControlError> {
+        self.animations(entity)?.seek(name, seconds)
+    }
+
+    pub fn is_playing(&mut self, entity: Entity, name: &str) -> Result<bool, AnimationControlError> {
+        self.animations(entity)?.is_playing(name)
+    }
+
+    pub fn is_finished(&mut self, entity: Entity, name: &str) -> Result<bool, AnimationControlError> {
+        self.animations(entity)?.is_finished(name)
+    }
+}
+
+/// The animations of one entity (blueprint or instance ones), see [`BlueprintAnimationControl`]
+pub struct AnimationsMut<'a> {
+    player: &'a mut AnimationPlayer,
+    transitions: Option<&'a mut AnimationTransitions>,
+    named_indices: &'a HashMap<String, AnimationNodeIndex>,
+}
+
+impl<'a> AnimationsMut<'a> {
+    pub fn index(&self, name: &str) -> Result<AnimationNodeIndex, AnimationControlError> {
+        self.named_indices
+            .get(name)
+            .copied()
+            .ok_or_else(|| AnimationControlError::UnknownAnimation(name.to_string()))
+    }
+
+    /// the names of the available animations
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.named_indices.keys().map(String::as_str)
+    }
+
+    /// plays the animation from the start, stopping the others: use the returned `ActiveAnimation` to ie `repeat()` it
+    pub fn play(self, name: &str) -> Result<&'a mut ActiveAnimation, AnimationControlError> {
+        self.crossfade(name, Duration::ZERO)
+    }
+
+    /// plays the animation from the start, while fading out the others over `duration`
+    pub fn crossfade(self, name: &str, duration: Duration) -> Result<&'a mut ActiveAnimation, AnimationControlError> {
+        let index = self.index(name)?;
+        match self.transitions {
+            Some(transitions) if !duration.is_zero() => Ok(transitions.play(self.player, index, duration)),
+            transitions => {
+                self.player.stop_all();
+                match transitions {
+                    // let the transitions know about the new main animation, without anything to fade out
+                    Some(transitions) => Ok(transitions.play(self.player, index, Duration::ZERO)),
+                    None => Ok(self.player.start(index)),
+                }
+            }
+        }
+    }
+
+    pub fn stop(self, name: &str) -> Result<(), AnimationControlError> {
+        let index = self.index(name)?;
+        self.player.stop(index);
+        Ok(())
+    }
+
+    pub fn stop_all(self) {
+        self.player.stop_all();
+    }
+
+    /// sets the playback speed of a playing animation (negative to play it backwards)
+    pub fn set_speed(self, name: &str, speed: f32) -> Result<(), AnimationControlError> {
+        let index = self.index(name)?;
+        if let Some(animation) = self.player.animation_mut(index) {
+            animation.set_speed(speed);
+        }
+        Ok(())
+    }
+
+    /// moves a playing animation to the given time (in seconds)
+    pub fn seek(self, name: &str, seconds: f32) -> Result<(), AnimationControlError> {
+        let index = self.index(name)?;
+        if let Some(animation) = self.player.animation_mut(index) {
+            animation.seek_to(seconds);
+        }
+        Ok(())
+    }
+
+    pub fn is_playing(&self, name: &str) -> Result<bool, AnimationControlError> {
+        Ok(self.player.is_playing_animation(self.index(name)?))
+    }
+
+    /// true if the animation has played all its repetitions, or is not playing at all
+    pub fn is_finished(&self, name: &str) -> Result<bool, AnimationControlError> {
+        let index = self.index(name)?;
+        Ok(self
+            .player
+            .animation(index)
+            .is_none_or(ActiveAnimation::is_finished))
+    }
+}
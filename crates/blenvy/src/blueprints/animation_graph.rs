@@ -0,0 +1,222 @@
+use bevy::{
+    animation::{AnimationTarget, AnimationTargetId},
+    gltf::Gltf,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::BlueprintAnimations;
+use crate::BlenvyComponent;
+
+/// The animations & animation graph of each blueprint (keyed by path), shared by all its instances:
+/// entries get removed when the blueprint changes (hot reload), & rebuilt the next time an instance of it is spawned
+#[derive(Resource, Default, Debug)]
+pub struct BlueprintAnimationGraphCache {
+    pub(crate) animations: HashMap<String, BlueprintAnimations>,
+    /// the blueprints whose graph has been built from their `AnimationGraphInfos`
+    pub(crate) from_graph_infos: HashSet<String>,
+}
+
+impl BlueprintAnimationGraphCache {
+    pub fn get(&self, blueprint_path: &str) -> Option<&BlueprintAnimations> {
+        self.animations.get(blueprint_path)
+    }
+
+    pub fn invalidate(&mut self, blueprint_path: &str) {
+        self.animations.remove(blueprint_path);
+        self.from_graph_infos.remove(blueprint_path);
+    }
+
+    pub fn clear(&mut self) {
+        self.animations.clear();
+        self.from_graph_infos.clear();
+    }
+}
+
+/// Describes the animation graph of a blueprint: gets added (on the Blender side) next to its `AnimationInfos`.
+/// Without it, all the animations are added to the root of a flat graph
+#[derive(Component, Reflect, Default, Debug, Clone, BlenvyComponent)]
+#[reflect(Component)]
+pub struct AnimationGraphInfos {
+    /// the nodes of the graph, parents before their children
+    pub nodes: Vec<AnimationGraphNodeInfo>,
+    /// named groups of bones that nodes can be masked with, ie `"UpperBody": ["Spine"]`:
+    /// each bone is part of the group along with all of its descendants
+    #[reflect(default)]
+    pub bone_groups: HashMap<String, Vec<String>>,
+    /// the animations (node names) that start playing, on repeat, as soon as the blueprint instance is spawned:
+    /// the first one is played through `AnimationTransitions` (so crossfading to another animation fades it out),
+    /// the others are layered on top of it & keep playing
+    #[reflect(default)]
+    pub default_animations: Vec<String>,
+}
+
+#[derive(Reflect, Debug, Clone)]
+pub struct AnimationGraphNodeInfo {
+    /// the name of the node, to refer to it as a parent & to play it: defaults to the name of its animation for clip nodes
+    #[reflect(default)]
+    pub name: String,
+    pub kind: AnimationGraphNodeKind,
+    /// the name of the parent node, the root of the graph if none
+    #[reflect(default)]
+    pub parent: Option<String>,
+    #[reflect(default = "default_weight")]
+    pub weight: f32,
+    /// the bone groups this node & its children do NOT animate
+    #[reflect(default)]
+    pub masked_groups: Vec<String>,
+}
+
+impl Default for AnimationGraphNodeInfo {
+    fn default() -> Self {
+        AnimationGraphNodeInfo {
+            name: String::new(),
+            kind: AnimationGraphNodeKind::default(),
+            parent: None,
+            weight: default_weight(),
+            masked_groups: vec![],
+        }
+    }
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+#[derive(Reflect, Default, Debug, Clone, PartialEq)]
+pub enum AnimationGraphNodeKind {
+    /// plays the animation with the given name
+    Clip(String),
+    /// blends its children according to their weights
+    #[default]
+    Blend,
+    /// adds its children on top of the first one (additive layers)
+    Additive,
+}
+
+impl AnimationGraphNodeInfo {
+    fn node_name(&self) -> &str {
+        match &self.kind {
+            AnimationGraphNodeKind::Clip(animation_name) if self.name.is_empty() => animation_name,
+            _ => &self.name,
+        }
+    }
+}
+
+/// the animation targets (bones) of each bone group, found among the descendants of `root`
+pub(crate) fn bone_group_targets(
+    graph_infos: &AnimationGraphInfos,
+    root: Entity,
+    all_children: &Query<&Children>,
+    animation_targets: &Query<(&Name, &AnimationTarget)>,
+) -> HashMap<String, Vec<AnimationTargetId>> {
+    let mut group_targets: HashMap<String, Vec<AnimationTargetId>> = HashMap::new();
+    for bone in all_children.iter_descendants(root) {
+        let Ok((name, _)) = animation_targets.get(bone) else {
+            continue;
+        };
+        for (group_name, bone_names) in graph_infos.bone_groups.iter() {
+            if !bone_names.iter().any(|bone_name| bone_name == name.as_str()) {
+                continue;
+            }
+            let targets = group_targets.entry(group_name.clone()).or_default();
+            for entity in std::iter::once(bone).chain(all_children.iter_descendants(bone)) {
+                if let Ok((_, target)) = animation_targets.get(entity) {
+                    targets.push(target.id);
+                }
+            }
+        }
+    }
+    group_targets
+}
+
+/// builds the animation graph described by `graph_infos`, returns it along with the indices of its named nodes:
+/// animations not used by any node are added to the root of the graph, so that they can still be played
+pub(crate) fn build_animation_graph(
+    graph_infos: &AnimationGraphInfos,
+    named_animations: &HashMap<String, Handle<AnimationClip>>,
+    group_targets: &HashMap<String, Vec<AnimationTargetId>>,
+) -> (AnimationGraph, HashMap<String, AnimationNodeIndex>) {
+    let mut graph = AnimationGraph::new();
+    let mut named_indices: HashMap<String, AnimationNodeIndex> = HashMap::new();
+
+    // mask groups are numbered in alphabetical order of their names
+    let mut group_names: Vec<&String> = graph_infos.bone_groups.keys().collect();
+    group_names.sort();
+    if group_names.len() > 64 {
+        warn!("Animation graphs support at most 64 bone groups, ignoring {}", group_names.len() - 64);
+        group_names.truncate(64);
+    }
+    for (group, group_name) in group_names.iter().enumerate() {
+        match group_targets.get(*group_name) {
+            Some(targets) => {
+                for target in targets {
+                    graph.add_target_to_mask_group(*target, group as u32);
+                }
+            }
+            None => warn!("No bones found for bone group {}", group_name),
+        }
+    }
+
+    let mut used_animations: Vec<&str> = vec![];
+    for node_info in graph_infos.nodes.iter() {
+        let name = node_info.node_name();
+        let parent = match &node_info.parent {
+            Some(parent_name) => match named_indices.get(parent_name) {
+                Some(parent) => *parent,
+                None => {
+                    warn!("Unknown parent {} for animation graph node {}, nodes must come after their parent", parent_name, name);
+                    continue;
+                }
+            },
+            None => graph.root,
+        };
+        let mut mask = 0;
+        for group_name in node_info.masked_groups.iter() {
+            match group_names.iter().position(|name| *name == group_name) {
+                Some(group) => mask |= 1 << group,
+                None => warn!("Unknown bone group {} for animation graph node {}", group_name, name),
+            }
+        }
+
+        let index = match &node_info.kind {
+            AnimationGraphNodeKind::Clip(animation_name) => {
+                let Some(clip) = named_animations.get(animation_name) else {
+                    warn!("Unknown animation {} for animation graph node {}", animation_name, name);
+                    continue;
+                };
+                used_animations.push(animation_name);
+                graph.add_clip_with_mask(clip.clone(), mask, node_info.weight, parent)
+            }
+            AnimationGraphNodeKind::Blend => graph.add_blend_with_mask(mask, node_info.weight, parent),
+            AnimationGraphNodeKind::Additive => graph.add_additive_blend_with_mask(mask, node_info.weight, parent),
+        };
+        if named_indices.insert(name.to_string(), index).is_some() {
+            warn!("Duplicate animation graph node name {}", name);
+        }
+    }
+
+    for (animation_name, clip) in named_animations.iter() {
+        if !used_animations.contains(&animation_name.as_str()) && !named_indices.contains_key(animation_name) {
+            let index = graph.add_clip(clip.clone(), 1.0, graph.root);
+            named_indices.insert(animation_name.clone(), index);
+        }
+    }
+
+    (graph, named_indices)
+}
+
+pub(crate) fn invalidate_blueprint_animation_graphs(
+    mut gltf_events: EventReader<AssetEvent<Gltf>>,
+    mut graph_cache: ResMut<BlueprintAnimationGraphCache>,
+    asset_server: Res<AssetServer>,
+) {
+    for event in gltf_events.read() {
+        if let AssetEvent::Modified { id } | AssetEvent::Removed { id } = event {
+            if let Some(asset_path) = asset_server.get_path(*id) {
+                debug!("Invalidating animation graph of {}", asset_path);
+                graph_cache.invalidate(&asset_path.to_string());
+            }
+        }
+    }
+}
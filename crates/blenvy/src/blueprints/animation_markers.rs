@@ -0,0 +1,233 @@
+use bevy::{
+    prelude::*,
+    reflect::{serde::TypedReflectDeserializer, FromType, GetTypeRegistration, TypeRegistration, TypeRegistry},
+    utils::{HashMap, HashSet},
+};
+use serde::de::DeserializeSeed;
+
+use crate::{AnimationInfos, AnimationMarkerReached, AnimationMarkers, BlenvyRegistrationApp, BlueprintAnimationPlayerLink};
+
+/// Type data for events that can be used as (typed) animation markers, see [`BlenvyAnimationApp::register_animation_marker_event`]
+/// You can also add it with `#[reflect(AnimationMarker)]` (the event also needs to be registered)
+#[derive(Clone)]
+pub struct ReflectAnimationMarker {
+    add_to_clip: fn(&mut AnimationClip, f32, &dyn PartialReflect) -> bool,
+}
+
+impl ReflectAnimationMarker {
+    /// adds the event (built from the reflected value) to the clip at the given time, returns false if the value is not of the right type
+    pub fn add_to_clip(&self, clip: &mut AnimationClip, time: f32, value: &dyn PartialReflect) -> bool {
+        (self.add_to_clip)(clip, time, value)
+    }
+}
+
+impl<E: Event + Clone + FromReflect> FromType<E> for ReflectAnimationMarker {
+    fn from_type() -> Self {
+        ReflectAnimationMarker {
+            add_to_clip: |clip, time, value| match E::from_reflect(value) {
+                Some(event) => {
+                    clip.add_event(time, event);
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+}
+
+pub trait BlenvyAnimationApp {
+    /// Registers an event that can be used as an animation marker in Blender, by naming the marker after the event type
+    /// & its value, in the same format as components: ie `Footstep(foot: Left)` or `Damage(10.0)`.
+    /// These events get triggered on the entity with the `AnimationPlayer` (use observers), & are also sent as buffered events
+    fn register_animation_marker_event<E: Event + Clone + FromReflect + TypePath + GetTypeRegistration>(&mut self) -> &mut Self;
+}
+
+impl BlenvyAnimationApp for App {
+    fn register_animation_marker_event<E: Event + Clone + FromReflect + TypePath + GetTypeRegistration>(&mut self) -> &mut Self {
+        self.register_blenvy_type::<E>().add_event::<E>();
+        self.world()
+            .resource::<AppTypeRegistry>()
+            .write()
+            .register_type_data::<E, ReflectAnimationMarker>();
+        self.add_observer(|trigger: Trigger<E>, mut events: EventWriter<E>| {
+            events.send(trigger.event().clone());
+        })
+    }
+}
+
+/// the typed event of a marker, if its name matches a registered marker event (`TypeName` or `TypeName(value)`)
+fn marker_event<'a>(
+    marker_name: &str,
+    type_registry: &'a TypeRegistry,
+) -> Option<(&'a ReflectAnimationMarker, Box<dyn PartialReflect>)> {
+    let (type_name, value) = match marker_name.find('(') {
+        Some(index) => (marker_name[..index].trim(), marker_name[index..].trim()),
+        None => (marker_name.trim(), "()"),
+    };
+    let registration: &TypeRegistration = type_registry
+        .get_with_type_path(type_name)
+        .or_else(|| type_registry.get_with_short_type_path(type_name))?;
+    let reflect_marker = registration.data::<ReflectAnimationMarker>()?;
+
+    let mut deserializer = match ron::Deserializer::from_str(value) {
+        Ok(deserializer) => deserializer,
+        Err(e) => {
+            warn!("Invalid value for animation marker '{}': {:?}", marker_name, e);
+            return None;
+        }
+    };
+    match TypedReflectDeserializer::new(registration, type_registry).deserialize(&mut deserializer) {
+        Ok(value) => Some((reflect_marker, value)),
+        Err(e) => {
+            warn!("Failed to deserialize animation marker '{}': {:?}", marker_name, e);
+            None
+        }
+    }
+}
+
+/// the event added to the clips for every marker: it is triggered on the entity with the `AnimationPlayer`,
+/// & only forwarded as an `AnimationMarkerReached` event once the blueprint instance it belongs to is known,
+/// see [`forward_animation_marker_events`]
+#[derive(Event, Debug, Clone)]
+pub(crate) struct AnimationMarkerClipEvent {
+    animation_name: String,
+    frame: u32,
+    marker_name: String,
+}
+
+/// the animation clips that already got their marker events
+#[derive(Resource, Debug, Default)]
+pub(crate) struct AnimationMarkerClips(HashSet<AssetId<AnimationClip>>);
+
+/// adds the markers of the animations as events of their clips (once per clip): an `AnimationMarkerClipEvent` for all of them
+/// & the typed events for those matching a registered marker event.
+/// Called while spawning blueprints, once their animations & components are available
+pub(crate) fn add_markers_to_clips(
+    named_animations: &HashMap<String, Handle<AnimationClip>>,
+    animation_infos: &AnimationInfos,
+    animation_markers: &AnimationMarkers,
+    animation_clips: &mut Assets<AnimationClip>,
+    type_registry: &TypeRegistry,
+    processed_clips: &mut AnimationMarkerClips,
+) {
+    for (animation_name, markers) in animation_markers.0.iter() {
+        let Some(clip_handle) = named_animations.get(animation_name) else {
+            continue;
+        };
+        if processed_clips.0.contains(&clip_handle.id()) {
+            continue;
+        }
+        let Some(frames_length) = animation_infos
+            .animations
+            .iter()
+            .find(|animation_info| &animation_info.name == animation_name)
+            .map(|animation_info| animation_info.frames_length)
+            .filter(|frames_length| *frames_length > 0.0)
+        else {
+            warn!("No animation infos for the markers of animation {}", animation_name);
+            continue;
+        };
+        let Some(clip) = animation_clips.get_mut(clip_handle) else {
+            continue;
+        };
+
+        let duration = clip.duration();
+        for (frame, marker_names) in markers.iter() {
+            let time = *frame as f32 * duration / frames_length;
+            for marker_name in marker_names {
+                debug!("adding marker {} of {} at {}s", marker_name, animation_name, time);
+                clip.add_event(
+                    time,
+                    AnimationMarkerClipEvent {
+                        animation_name: animation_name.clone(),
+                        frame: *frame,
+                        marker_name: marker_name.clone(),
+                    },
+                );
+                if let Some((reflect_marker, value)) = marker_event(marker_name, type_registry) {
+                    if !reflect_marker.add_to_clip(clip, time, value.as_ref()) {
+                        warn!("Animation marker '{}' could not be converted to its event type", marker_name);
+                    }
+                }
+            }
+        }
+        processed_clips.0.insert(clip_handle.id());
+    }
+}
+
+/// reloaded clips lose their events: they get added again when the blueprints using them are respawned
+pub(crate) fn invalidate_animation_marker_clips(
+    mut gltf_events: EventReader<AssetEvent<Gltf>>,
+    assets_gltf: Res<Assets<Gltf>>,
+    mut processed_clips: ResMut<AnimationMarkerClips>,
+) {
+    for event in gltf_events.read() {
+        if let AssetEvent::Modified { id } = event {
+            if let Some(gltf) = assets_gltf.get(*id) {
+                for clip in gltf.animations.iter() {
+                    processed_clips.0.remove(&clip.id());
+                }
+            }
+        }
+    }
+}
+
+/// marker events are triggered on the entity with the `AnimationPlayer`: sends them as buffered `AnimationMarkerReached` events,
+/// with the entity linked to that animation player (ie the blueprint instance), or the animation player itself
+pub(crate) fn forward_animation_marker_events(
+    trigger: Trigger<AnimationMarkerClipEvent>,
+    player_links: Query<&BlueprintAnimationPlayerLink>,
+    parents: Query<&Parent>,
+    mut animation_marker_events: EventWriter<AnimationMarkerReached>,
+) {
+    let player = trigger.entity();
+    let entity = std::iter::once(player)
+        .chain(parents.iter_ancestors(player))
+        .find(|entity| player_links.get(*entity).is_ok_and(|link| link.0 == player))
+        .unwrap_or(player);
+    let marker = trigger.event();
+    animation_marker_events.send(AnimationMarkerReached {
+        entity,
+        animation_name: marker.animation_name.clone(),
+        frame: marker.frame,
+        marker_name: marker.marker_name.clone(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marker_events_are_sent_for_the_blueprint_instance() {
+        let mut world = World::new();
+        world.init_resource::<Events<AnimationMarkerReached>>();
+        world.add_observer(forward_animation_marker_events);
+
+        let player = world.spawn_empty().id();
+        let instance = world.spawn(BlueprintAnimationPlayerLink(player)).add_child(player).id();
+        // animation players without a blueprint instance
+        let standalone_player = world.spawn_empty().id();
+
+        let marker = AnimationMarkerClipEvent {
+            animation_name: "Walk".into(),
+            frame: 12,
+            marker_name: "footstep".into(),
+        };
+        world.entity_mut(player).trigger(marker.clone());
+        world.entity_mut(standalone_player).trigger(marker);
+        world.flush();
+
+        let events = world.resource::<Events<AnimationMarkerReached>>();
+        let events: Vec<&AnimationMarkerReached> = events.iter_current_update_events().collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].entity, instance);
+        assert_eq!(events[1].entity, standalone_player);
+        for event in events {
+            assert_eq!(
+                (event.animation_name.as_str(), event.frame, event.marker_name.as_str()),
+                ("Walk", 12, "footstep")
+            );
+        }
+    }
+}
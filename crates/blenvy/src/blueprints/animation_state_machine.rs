@@ -0,0 +1,375 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{AnimationControlError, BlenvyComponent, BlueprintAnimationControl};
+
+/// Data driven animation logic, authored (on the Blender side) on blueprints or blueprint instances:
+/// plays the animation of the current state, & moves to other states (crossfading their animations) when the conditions
+/// of a transition are met, based on the `AnimationParameters` set from gameplay code
+#[derive(Component, Reflect, Default, Debug, Clone, BlenvyComponent)]
+#[reflect(Component)]
+pub struct AnimationStateMachine {
+    pub states: Vec<AnimationStateInfo>,
+    /// checked in order, the first one with all its conditions met is taken
+    pub transitions: Vec<AnimationStateTransition>,
+    /// defaults to the first state
+    #[reflect(default)]
+    pub initial_state: Option<String>,
+}
+
+#[derive(Reflect, Debug, Clone)]
+pub struct AnimationStateInfo {
+    pub name: String,
+    /// the name of the animation (from `BlueprintAnimations` or `InstanceAnimations`) to play in this state
+    pub animation: String,
+    #[reflect(default = "default_repeat")]
+    pub repeat: bool,
+    #[reflect(default = "default_speed")]
+    pub speed: f32,
+}
+
+impl Default for AnimationStateInfo {
+    fn default() -> Self {
+        AnimationStateInfo {
+            name: String::new(),
+            animation: String::new(),
+            repeat: default_repeat(),
+            speed: default_speed(),
+        }
+    }
+}
+
+fn default_repeat() -> bool {
+    true
+}
+
+fn default_speed() -> f32 {
+    1.0
+}
+
+#[derive(Reflect, Default, Debug, Clone)]
+pub struct AnimationStateTransition {
+    /// the state this transition starts from, any state if none
+    #[reflect(default)]
+    pub from: Option<String>,
+    pub to: String,
+    /// all of them need to be met for the transition to be taken
+    #[reflect(default)]
+    pub conditions: Vec<AnimationCondition>,
+    /// crossfade duration, in seconds
+    #[reflect(default)]
+    pub duration: f32,
+}
+
+#[derive(Reflect, Debug, Clone, PartialEq)]
+pub enum AnimationCondition {
+    /// the float parameter is greater than the value
+    Greater(String, f32),
+    /// the float parameter is less than the value
+    Less(String, f32),
+    IsTrue(String),
+    IsFalse(String),
+    /// the trigger parameter has been set: it gets reset when the transition is taken
+    Triggered(String),
+    /// the animation of the current state has finished playing
+    AnimationFinished,
+}
+
+impl AnimationCondition {
+    /// `finished`: whether the animation of the current state has finished playing
+    pub fn is_met(&self, parameters: &AnimationParameters, finished: bool) -> bool {
+        match self {
+            AnimationCondition::Greater(name, value) => parameters.float(name).is_some_and(|parameter| parameter > *value),
+            AnimationCondition::Less(name, value) => parameters.float(name).is_some_and(|parameter| parameter < *value),
+            AnimationCondition::IsTrue(name) => parameters.bool(name) == Some(true),
+            AnimationCondition::IsFalse(name) => parameters.bool(name) == Some(false),
+            AnimationCondition::Triggered(name) => parameters.is_triggered(name),
+            AnimationCondition::AnimationFinished => finished,
+        }
+    }
+}
+
+#[derive(Reflect, Debug, Clone, Copy, PartialEq)]
+pub enum AnimationParameter {
+    Float(f32),
+    Bool(bool),
+    Trigger(bool),
+}
+
+/// The parameters driving an `AnimationStateMachine`, set from gameplay code, ie
+/// ```rust ignore
+/// fn locomotion(mut players: Query<(&Velocity, &mut AnimationParameters)>) {
+///     for (velocity, mut parameters) in players.iter_mut() {
+///         parameters.set_float("speed", velocity.length());
+///     }
+/// }
+/// ```
+/// Gets added automatically to entities with a state machine, & can also be authored in Blender (ie with default values)
+#[derive(Component, Reflect, Default, Debug, Clone, BlenvyComponent)]
+#[reflect(Component)]
+pub struct AnimationParameters(pub HashMap<String, AnimationParameter>);
+
+impl AnimationParameters {
+    pub fn set_float(&mut self, name: impl Into<String>, value: f32) {
+        self.0.insert(name.into(), AnimationParameter::Float(value));
+    }
+
+    pub fn set_bool(&mut self, name: impl Into<String>, value: bool) {
+        self.0.insert(name.into(), AnimationParameter::Bool(value));
+    }
+
+    /// sets a trigger, that stays set until a transition using it is taken
+    pub fn set_trigger(&mut self, name: impl Into<String>) {
+        self.0.insert(name.into(), AnimationParameter::Trigger(true));
+    }
+
+    pub fn float(&self, name: &str) -> Option<f32> {
+        match self.0.get(name) {
+            Some(AnimationParameter::Float(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn bool(&self, name: &str) -> Option<bool> {
+        match self.0.get(name) {
+            Some(AnimationParameter::Bool(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn is_triggered(&self, name: &str) -> bool {
+        matches!(self.0.get(name), Some(AnimationParameter::Trigger(true)))
+    }
+
+    fn reset_trigger(&mut self, name: &str) {
+        if let Some(parameter @ AnimationParameter::Trigger(_)) = self.0.get_mut(name) {
+            *parameter = AnimationParameter::Trigger(false);
+        }
+    }
+}
+
+/// The current state of an `AnimationStateMachine`: gets added once it has entered its initial state
+#[derive(Component, Reflect, Debug, Clone, PartialEq, Eq)]
+#[reflect(Component)]
+pub struct CurrentAnimationState(pub String);
+
+impl AnimationStateMachine {
+    pub fn state(&self, name: &str) -> Option<&AnimationStateInfo> {
+        self.states.iter().find(|state| state.name == name)
+    }
+
+    fn initial_state(&self) -> Option<&AnimationStateInfo> {
+        match &self.initial_state {
+            Some(name) => self.state(name),
+            None => self.states.first(),
+        }
+    }
+
+    /// the first transition out of the given state with all its conditions met, if any
+    pub fn next_transition(
+        &self,
+        state: &str,
+        parameters: &AnimationParameters,
+        finished: bool,
+    ) -> Option<&AnimationStateTransition> {
+        self.transitions.iter().find(|transition| {
+            transition.from.as_ref().is_none_or(|from| from == state)
+                && transition.to != state
+                && transition
+                    .conditions
+                    .iter()
+                    .all(|condition| condition.is_met(parameters, finished))
+        })
+    }
+}
+
+/// the crossfade duration of a transition, falls back to no crossfade for durations that are not representable
+fn crossfade_duration(entity: Entity, transition: &AnimationStateTransition) -> Duration {
+    Duration::try_from_secs_f32(transition.duration.max(0.0)).unwrap_or_else(|error| {
+        warn!(
+            "Animation state machine of {:?}: invalid duration {} for the transition to {} ({})",
+            entity, transition.duration, transition.to, error
+        );
+        Duration::ZERO
+    })
+}
+
+fn enter_state(
+    entity: Entity,
+    state: &AnimationStateInfo,
+    duration: Duration,
+    animation_control: &mut BlueprintAnimationControl,
+) -> Result<(), AnimationControlError> {
+    let animation = animation_control.crossfade(entity, &state.animation, duration)?;
+    animation.set_speed(state.speed);
+    if state.repeat {
+        animation.repeat();
+    }
+    Ok(())
+}
+
+pub(crate) fn update_animation_state_machines(
+    mut state_machines: Query<(
+        Entity,
+        &AnimationStateMachine,
+        Option<&mut AnimationParameters>,
+        Option<&mut CurrentAnimationState>,
+    )>,
+    mut animation_control: BlueprintAnimationControl,
+    mut commands: Commands,
+) {
+    for (entity, state_machine, parameters, current_state) in state_machines.iter_mut() {
+        let (Some(mut parameters), Some(mut current_state)) = (parameters, current_state) else {
+            // not started yet: wait for the animations to be available
+            match animation_control.animations(entity) {
+                Err(AnimationControlError::NoAnimations(..)) | Err(AnimationControlError::NoAnimationPlayer(_)) => continue,
+                _ => {}
+            }
+            let Some(initial_state) = state_machine.initial_state() else {
+                warn!("Animation state machine of {:?} has no initial state", entity);
+                continue;
+            };
+            if let Err(error) = enter_state(entity, initial_state, Duration::ZERO, &mut animation_control) {
+                warn!("Animation state machine of {:?}: {}", entity, error);
+            }
+            commands
+                .entity(entity)
+                .insert(CurrentAnimationState(initial_state.name.clone()));
+            commands.entity(entity).entry::<AnimationParameters>().or_default();
+            continue;
+        };
+
+        let Some(state) = state_machine.state(&current_state.0) else {
+            continue;
+        };
+        let finished = animation_control
+            .is_finished(entity, &state.animation)
+            .unwrap_or(false);
+        let Some(transition) = state_machine.next_transition(&state.name, &parameters, finished) else {
+            continue;
+        };
+        let Some(next_state) = state_machine.state(&transition.to) else {
+            warn!("Animation state machine of {:?}: unknown state {}", entity, transition.to);
+            continue;
+        };
+
+        for condition in transition.conditions.iter() {
+            if let AnimationCondition::Triggered(name) = condition {
+                parameters.reset_trigger(name);
+            }
+        }
+        let duration = crossfade_duration(entity, transition);
+        if let Err(error) = enter_state(entity, next_state, duration, &mut animation_control) {
+            warn!("Animation state machine of {:?}: {}", entity, error);
+        }
+        current_state.0 = next_state.name.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transition(from: Option<&str>, to: &str, conditions: Vec<AnimationCondition>) -> AnimationStateTransition {
+        AnimationStateTransition {
+            from: from.map(str::to_string),
+            to: to.to_string(),
+            conditions,
+            duration: 0.2,
+        }
+    }
+
+    fn state_machine() -> AnimationStateMachine {
+        let state = |name: &str| AnimationStateInfo {
+            name: name.to_string(),
+            animation: name.to_string(),
+            ..default()
+        };
+        AnimationStateMachine {
+            states: vec![state("Idle"), state("Walk"), state("Jump")],
+            transitions: vec![
+                transition(Some("Idle"), "Walk", vec![AnimationCondition::Greater("speed".into(), 0.1)]),
+                transition(Some("Walk"), "Idle", vec![AnimationCondition::Less("speed".into(), 0.1)]),
+                transition(
+                    None,
+                    "Jump",
+                    vec![
+                        AnimationCondition::Triggered("jump".into()),
+                        AnimationCondition::IsTrue("grounded".into()),
+                    ],
+                ),
+                transition(Some("Jump"), "Idle", vec![AnimationCondition::AnimationFinished]),
+            ],
+            initial_state: None,
+        }
+    }
+
+    #[test]
+    fn conditions() {
+        let mut parameters = AnimationParameters::default();
+        parameters.set_float("speed", 2.0);
+        parameters.set_bool("grounded", true);
+
+        assert!(AnimationCondition::Greater("speed".into(), 1.0).is_met(&parameters, false));
+        assert!(!AnimationCondition::Greater("speed".into(), 2.0).is_met(&parameters, false));
+        assert!(AnimationCondition::Less("speed".into(), 3.0).is_met(&parameters, false));
+        assert!(AnimationCondition::IsTrue("grounded".into()).is_met(&parameters, false));
+        assert!(!AnimationCondition::IsFalse("grounded".into()).is_met(&parameters, false));
+        // missing parameters, or parameters of another kind, never meet a condition
+        assert!(!AnimationCondition::Greater("height".into(), -1.0).is_met(&parameters, false));
+        assert!(!AnimationCondition::IsFalse("speed".into()).is_met(&parameters, false));
+        assert!(!AnimationCondition::Greater("grounded".into(), -1.0).is_met(&parameters, false));
+
+        assert!(!AnimationCondition::Triggered("jump".into()).is_met(&parameters, false));
+        parameters.set_trigger("jump");
+        assert!(AnimationCondition::Triggered("jump".into()).is_met(&parameters, false));
+        parameters.reset_trigger("jump");
+        assert!(!AnimationCondition::Triggered("jump".into()).is_met(&parameters, false));
+
+        assert!(AnimationCondition::AnimationFinished.is_met(&parameters, true));
+        assert!(!AnimationCondition::AnimationFinished.is_met(&parameters, false));
+    }
+
+    #[test]
+    fn transition_selection() {
+        let state_machine = state_machine();
+        let next = |state: &str, parameters: &AnimationParameters, finished: bool| {
+            state_machine
+                .next_transition(state, parameters, finished)
+                .map(|transition| transition.to.as_str())
+        };
+        let mut parameters = AnimationParameters::default();
+        assert_eq!(next("Idle", &parameters, false), None);
+
+        parameters.set_float("speed", 1.0);
+        assert_eq!(next("Idle", &parameters, false), Some("Walk"));
+        // transitions from another state are ignored
+        assert_eq!(next("Walk", &parameters, false), None);
+
+        // all the conditions need to be met
+        parameters.set_trigger("jump");
+        assert_eq!(next("Walk", &parameters, false), None);
+        parameters.set_bool("grounded", true);
+        assert_eq!(next("Walk", &parameters, false), Some("Jump"));
+        // the first matching transition wins
+        assert_eq!(next("Idle", &parameters, false), Some("Walk"));
+        // transitions without a `from` state do not loop back into their target state
+        assert_eq!(next("Jump", &parameters, false), None);
+        assert_eq!(next("Jump", &parameters, true), Some("Idle"));
+    }
+
+    #[test]
+    fn crossfade_durations() {
+        let entity = Entity::from_raw(0);
+        let with_duration = |duration: f32| AnimationStateTransition {
+            duration,
+            ..transition(None, "Idle", vec![])
+        };
+        assert_eq!(crossfade_duration(entity, &with_duration(0.5)), Duration::from_millis(500));
+        assert_eq!(crossfade_duration(entity, &with_duration(-1.0)), Duration::ZERO);
+        assert_eq!(crossfade_duration(entity, &with_duration(f32::NAN)), Duration::ZERO);
+        assert_eq!(crossfade_duration(entity, &with_duration(f32::INFINITY)), Duration::ZERO);
+        assert_eq!(crossfade_duration(entity, &with_duration(f32::MAX)), Duration::ZERO);
+    }
+}
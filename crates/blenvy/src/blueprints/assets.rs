@@ -1,6 +1,10 @@
+use std::path::Path;
+
 use bevy::{asset::LoadedUntypedAsset, prelude::*};
 use serde::Deserialize;
 
+use crate::BlenvyComponent;
+
 /// helper component, is used to store the list of sub blueprints to enable automatic loading of dependend blueprints
 #[derive(Component, Reflect, Default, Debug, Deserialize)]
 #[reflect(Component)]
@@ -11,7 +15,7 @@ pub struct BlueprintAsset {
 
 /// helper component, is used to store the list of sub blueprints to enable automatic loading of dependend blueprints
 /// these are only the DIRECT dependencies of a blueprint, does not contain the indirect assets (ie assets of sub blueprints, etc)
-#[derive(Component, Reflect, Default, Debug, Deserialize)]
+#[derive(Component, Reflect, Default, Debug, Deserialize, BlenvyComponent)]
 #[reflect(Component)]
 pub struct BlueprintAssets {
     /// only this field should get filled in from the Blender side
@@ -77,6 +81,15 @@ pub(crate) struct BlueprintPreloadAssets {
     pub(crate) assets: Vec<(String, File)>,
 }
 
+/// path of the `.meta.ron` file listing the assets of a blueprint/level, next to it:
+/// ie `levels/World.glb` => `levels/World.meta.ron`
+pub fn blueprint_meta_path(blueprint_path: &str) -> String {
+    Path::new(blueprint_path)
+        .with_extension("meta.ron")
+        .to_string_lossy()
+        .into_owned()
+}
+
 #[derive(Component)]
 pub(crate) struct BlueprintMetaHandle(pub Handle<BlueprintPreloadAssets>);
 
@@ -0,0 +1,310 @@
+use std::marker::PhantomData;
+
+use bevy::{gltf::GltfMaterialName, prelude::*, utils::HashMap};
+
+use crate::{
+    library_material, BlenvyComponent, BlenvyMaterialConfig, BlueprintInstanceReady,
+    LibraryMaterial, MaterialAssets, MaterialInfo,
+};
+
+/// The material variants of a blueprint (usually authored in Blender):
+/// variant name => (name of the material to replace => replacement material from a material library)
+#[derive(Component, Reflect, Default, Debug, Clone, BlenvyComponent)]
+#[reflect(Component)]
+pub struct MaterialVariants(pub HashMap<String, HashMap<String, MaterialInfo>>);
+
+/// Selects one of the [`MaterialVariants`] of a blueprint instance, ie `MaterialVariant("damaged".into())`:
+/// the materials of all its descendants (up to nested blueprint instances with their own `MaterialVariant`) get swapped.
+/// It can be changed or removed at runtime, removing it restores the original materials.
+/// Works for `StandardMaterial`s & the material types enabled with [`crate::BlenvyMaterialApp::register_blenvy_material`]
+#[derive(Component, Reflect, Default, Debug, Clone, PartialEq, Eq, Hash, BlenvyComponent)]
+#[reflect(Component)]
+pub struct MaterialVariant(pub String);
+
+impl MaterialVariant {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+}
+
+/// the material a mesh had before a variant was applied, to be able to restore it
+#[derive(Component)]
+pub(crate) struct VariantBaseMaterial<M: Material>(pub(crate) Handle<M>);
+
+/// for instances whose variant could not be applied yet: keeps the material libraries that are still loading alive
+/// until their materials have been cached
+#[derive(Component)]
+pub(crate) struct MaterialVariantPending<M: Material>(Vec<Handle<Gltf>>, PhantomData<M>);
+
+/// the meshes affected by the variant of an instance: all its descendants, except those of nested instances with their own variant
+fn variant_meshes(root: Entity, all_children: &Query<&Children>, with_variant: &Query<(), With<MaterialVariant>>) -> Vec<Entity> {
+    let mut meshes = vec![];
+    let mut stack = vec![root];
+    while let Some(entity) = stack.pop() {
+        meshes.push(entity);
+        if let Ok(children) = all_children.get(entity) {
+            stack.extend(children.iter().filter(|child| with_variant.get(**child).is_err()));
+        }
+    }
+    meshes
+}
+
+/// the instance whose `MaterialVariant` applies to the given mesh, if any: the closest one up the hierarchy
+pub(crate) fn variant_instance(
+    mesh: Entity,
+    parents: &Query<&Parent>,
+    with_variant: impl Fn(Entity) -> bool,
+) -> Option<Entity> {
+    std::iter::once(mesh)
+        .chain(parents.iter_ancestors(mesh))
+        .find(|entity| with_variant(*entity))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn apply_material_variants<M: Material>(
+    instances: Query<
+        (
+            Entity,
+            Ref<MaterialVariant>,
+            Option<&MaterialVariants>,
+            Option<&Name>,
+            Option<&MaterialVariantPending<M>>,
+        ),
+        (
+            With<BlueprintInstanceReady>,
+            Or<(
+                Changed<MaterialVariant>,
+                Added<BlueprintInstanceReady>,
+                With<MaterialVariantPending<M>>,
+            )>,
+        ),
+    >,
+    all_children: Query<&Children>,
+    with_variant: Query<(), With<MaterialVariant>>,
+    meshes: Query<(&MeshMaterial3d<M>, &GltfMaterialName, Option<&VariantBaseMaterial<M>>)>,
+    mut blenvy_config: ResMut<BlenvyMaterialConfig<M>>,
+    assets_gltf: Res<Assets<Gltf>>,
+    asset_server: Res<AssetServer>,
+    mut material_assets: MaterialAssets<M>,
+    mut commands: Commands,
+) {
+    let is_standard_material = std::any::TypeId::of::<M>() == std::any::TypeId::of::<StandardMaterial>();
+    for (entity, variant, variants, name, pending) in instances.iter() {
+        let still_loading = pending.is_some_and(|pending| {
+            pending
+                .0
+                .iter()
+                .any(|library| !assets_gltf.contains(library.id()) && !asset_server.load_state(library.id()).is_failed())
+        });
+        if still_loading && !variant.is_changed() {
+            continue;
+        }
+        let Some(replacements) = variants.and_then(|variants| variants.0.get(&variant.0)) else {
+            if is_standard_material {
+                warn!("Unknown material variant '{}' for {:?}", variant.0, name);
+            }
+            commands.entity(entity).remove::<MaterialVariantPending<M>>();
+            continue;
+        };
+
+        let mut loading_libraries = vec![];
+        let mut swaps = vec![];
+        for mesh_entity in variant_meshes(entity, &all_children, &with_variant) {
+            let Ok((material, material_name, base_material)) = meshes.get(mesh_entity) else {
+                continue;
+            };
+            let Some(material_info) = replacements.get(&material_name.0) else {
+                // not affected by this variant: restore its material if a previous variant changed it
+                if let Some(base_material) = base_material {
+                    swaps.push((mesh_entity, base_material.0.clone(), None));
+                }
+                continue;
+            };
+            let base_material = base_material.map_or(&material.0, |base_material| &base_material.0);
+            let library: Handle<Gltf> = asset_server.load(material_info.path.clone());
+            let cache_key = (library.id(), material_info.name.clone());
+            let replacement = match blenvy_config.materials_cache.get(&cache_key) {
+                Some(replacement) => Some(replacement.clone()),
+                None => match library_material(&library, material_info, &assets_gltf, &asset_server) {
+                    LibraryMaterial::Found(library_material) => {
+                        let replacement =
+                            material_assets.resolve(library_material, material_info, blenvy_config.convert.as_ref());
+                        if let Some(replacement) = replacement.as_ref() {
+                            blenvy_config.materials_cache.insert(cache_key, replacement.clone());
+                        }
+                        replacement
+                    }
+                    LibraryMaterial::Missing => None,
+                    LibraryMaterial::Loading => {
+                        loading_libraries.push(library);
+                        None
+                    }
+                },
+            };
+            swaps.push((mesh_entity, base_material.clone(), replacement));
+        }
+
+        // only swap once all the materials are available, to avoid partially applied variants
+        if !loading_libraries.is_empty() {
+            commands
+                .entity(entity)
+                .insert(MaterialVariantPending::<M>(loading_libraries, PhantomData));
+            continue;
+        }
+        debug!("Applying material variant '{}' to {:?}", variant.0, name);
+        for (mesh_entity, base_material, replacement) in swaps {
+            match replacement {
+                Some(replacement) => commands
+                    .entity(mesh_entity)
+                    .insert((MeshMaterial3d(replacement), VariantBaseMaterial(base_material))),
+                None => commands
+                    .entity(mesh_entity)
+                    .insert(MeshMaterial3d(base_material))
+                    .remove::<VariantBaseMaterial<M>>(),
+            };
+        }
+        commands.entity(entity).remove::<MaterialVariantPending<M>>();
+    }
+}
+
+/// restores the original materials of instances whose `MaterialVariant` was removed
+pub(crate) fn remove_material_variants<M: Material>(
+    mut removed_variants: RemovedComponents<MaterialVariant>,
+    all_children: Query<&Children>,
+    with_variant: Query<(), With<MaterialVariant>>,
+    base_materials: Query<&VariantBaseMaterial<M>>,
+    mut commands: Commands,
+) {
+    for entity in removed_variants.read() {
+        if let Some(mut entity_commands) = commands.get_entity(entity) {
+            entity_commands.remove::<MaterialVariantPending<M>>();
+        }
+        for mesh_entity in variant_meshes(entity, &all_children, &with_variant) {
+            if let Ok(base_material) = base_materials.get(mesh_entity) {
+                commands
+                    .entity(mesh_entity)
+                    .insert(MeshMaterial3d(base_material.0.clone()))
+                    .remove::<VariantBaseMaterial<M>>();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{asset::AssetPlugin, gltf::GltfMaterialName};
+
+    use super::*;
+    use crate::{inject_materials, react_to_material_library_changes, MaterialInfos};
+
+    fn material_library(materials: &[(&str, Handle<StandardMaterial>)]) -> Gltf {
+        Gltf {
+            scenes: default(),
+            named_scenes: default(),
+            meshes: default(),
+            named_meshes: default(),
+            materials: materials.iter().map(|(_, material)| material.clone()).collect(),
+            named_materials: materials
+                .iter()
+                .map(|(name, material)| (Box::from(*name), material.clone()))
+                .collect(),
+            nodes: default(),
+            named_nodes: default(),
+            skins: default(),
+            named_skins: default(),
+            default_scene: None,
+            animations: default(),
+            named_animations: default(),
+            source: None,
+        }
+    }
+
+    fn material(app: &App, entity: Entity) -> Handle<StandardMaterial> {
+        app.world()
+            .get::<MeshMaterial3d<StandardMaterial>>(entity)
+            .unwrap()
+            .0
+            .clone()
+    }
+
+    fn base_material(app: &App, entity: Entity) -> Option<Handle<StandardMaterial>> {
+        app.world()
+            .get::<VariantBaseMaterial<StandardMaterial>>(entity)
+            .map(|base_material| base_material.0.clone())
+    }
+
+    #[test]
+    fn variants_survive_material_library_reloads() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Gltf>()
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .init_resource::<BlenvyMaterialConfig>()
+            .add_systems(
+                Update,
+                (
+                    react_to_material_library_changes::<StandardMaterial>,
+                    inject_materials::<StandardMaterial>,
+                    apply_material_variants::<StandardMaterial>,
+                    remove_material_variants::<StandardMaterial>,
+                )
+                    .chain(),
+            );
+
+        let library_path = "materials/Library.glb";
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        let library: Handle<Gltf> = asset_server.load(library_path);
+        let add_materials = |app: &mut App| {
+            let mut materials = app.world_mut().resource_mut::<Assets<StandardMaterial>>();
+            let (red, blue) = (materials.add(Color::WHITE), materials.add(Color::BLACK));
+            let gltf = material_library(&[("Red", red.clone()), ("Blue", blue.clone())]);
+            app.world_mut().resource_mut::<Assets<Gltf>>().insert(&library, gltf);
+            (red, blue)
+        };
+        let (red, blue) = add_materials(&mut app);
+
+        let material_info = |name: &str| MaterialInfo {
+            path: library_path.into(),
+            name: name.into(),
+        };
+        let mesh = app
+            .world_mut()
+            .spawn((
+                Mesh3d(asset_server.load("blueprints/Car.glb#Mesh0/Primitive0")),
+                GltfMaterialName("Red".into()),
+                MeshMaterial3d::<StandardMaterial>::default(),
+            ))
+            .id();
+        let instance = app
+            .world_mut()
+            .spawn((
+                MaterialInfos(vec![material_info("Red")]),
+                MaterialVariants(HashMap::from([(
+                    "blue".to_string(),
+                    HashMap::from([("Red".to_string(), material_info("Blue"))]),
+                )])),
+                MaterialVariant::new("blue"),
+                BlueprintInstanceReady,
+            ))
+            .add_child(mesh)
+            .id();
+
+        app.update();
+        assert_eq!(material(&app, mesh), blue);
+        assert_eq!(base_material(&app, mesh), Some(red));
+
+        // the material library gets modified & reloaded
+        let (new_red, new_blue) = add_materials(&mut app);
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(material(&app, mesh), new_blue);
+        assert_eq!(base_material(&app, mesh), Some(new_red.clone()));
+
+        app.world_mut().entity_mut(instance).remove::<MaterialVariant>();
+        app.update();
+        assert_eq!(material(&app, mesh), new_red);
+        assert_eq!(base_material(&app, mesh), None);
+    }
+}
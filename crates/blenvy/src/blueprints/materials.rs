@@ -1,72 +1,379 @@
-use bevy::prelude::*;
+use std::{marker::PhantomData, sync::Arc};
 
-// Configuration resource for caching materials
-#[derive(Resource, Default)]
-pub struct BlenvyMaterialConfig {
-   pub materials_cache: std::collections::HashMap<String, Handle<StandardMaterial>>,
+use bevy::{ecs::system::SystemParam, gltf::GltfMaterialName, prelude::*, utils::HashMap};
+
+use super::hot_reload;
+use crate::{
+    apply_material_variants, blueprints_cleanup_spawned_scene, remove_material_variants, variant_instance,
+    BlenvyComponent, GltfBlueprintsSet, MaterialVariant, MaterialVariants, OriginalChildren, VariantBaseMaterial,
+};
+
+/// builds a custom material from the `StandardMaterial` found in the material library (or returns `None` if it does not handle it)
+pub type MaterialConverter<M> = Arc<dyn Fn(&StandardMaterial, &MaterialInfo) -> Option<M> + Send + Sync>;
+
+/// The material library for one material type: always initialised for `StandardMaterial`, see [`BlenvyMaterialApp`] for other material types.
+/// Entries are invalidated (and the materials re-injected) when their material library changes
+#[derive(Resource)]
+pub struct BlenvyMaterialConfig<M: Material = StandardMaterial> {
+    /// materials by material library (gltf) asset id & material name: you can also insert your own (custom) materials here directly
+    pub materials_cache: HashMap<(AssetId<Gltf>, String), Handle<M>>,
+    pub(crate) convert: Option<MaterialConverter<M>>,
+}
+
+impl<M: Material> Default for BlenvyMaterialConfig<M> {
+    fn default() -> Self {
+        Self {
+            materials_cache: Default::default(),
+            convert: None,
+        }
+    }
 }
 
 // Component to mark entities with material info
-#[derive(Reflect, Component, Default)]
+#[derive(Reflect, Component, Default, BlenvyComponent)]
 #[reflect(Component)]
-pub struct MaterialInfos(Vec<MaterialInfo>);
+pub struct MaterialInfos(pub(crate) Vec<MaterialInfo>);
 
-#[derive(Reflect, Component, Default)]
+#[derive(Reflect, Component, Default, Debug, Clone, PartialEq)]
 #[reflect(Component)]
 pub struct MaterialInfo {
-    path: String,
-    name: String,
+    pub path: String,
+    pub name: String,
 }
 
 // Component to mark entities that have had materials processed
 #[derive(Component)]
-pub(crate) struct MaterialProcessed;
+pub(crate) struct MaterialProcessed<M: Material>(PhantomData<M>);
+
+// Component to mark meshes that got a material from the material library, so that it does not get replaced by another material type
+#[derive(Component)]
+pub(crate) struct MaterialInjected;
+
+pub trait BlenvyMaterialApp {
+    /// Enables the material library for a custom material type (ie an `ExtendedMaterial`): `convert` creates it from the `StandardMaterial`
+    /// stored in the material library, or returns `None` to leave that material to the other material types.
+    /// Custom materials are injected before `StandardMaterial`s, & `MaterialVariant`s also apply to them
+    fn register_blenvy_material<M: Material>(
+        &mut self,
+        convert: impl Fn(&StandardMaterial, &MaterialInfo) -> Option<M> + Send + Sync + 'static,
+    ) -> &mut Self;
+}
+
+impl BlenvyMaterialApp for App {
+    fn register_blenvy_material<M: Material>(
+        &mut self,
+        convert: impl Fn(&StandardMaterial, &MaterialInfo) -> Option<M> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.insert_resource(BlenvyMaterialConfig::<M> {
+            materials_cache: Default::default(),
+            convert: Some(Arc::new(convert)),
+        })
+        .add_systems(
+            Update,
+            inject_materials::<M>
+                .after(blueprints_cleanup_spawned_scene)
+                .before(inject_materials::<StandardMaterial>)
+                .in_set(GltfBlueprintsSet::Spawn),
+        )
+        .add_systems(
+            Update,
+            (apply_material_variants::<M>, remove_material_variants::<M>).in_set(GltfBlueprintsSet::AfterSpawn),
+        )
+        .add_systems(Update, react_to_material_library_changes::<M>.run_if(hot_reload))
+    }
+}
+
+#[derive(SystemParam)]
+pub(crate) struct MaterialAssets<'w, 's, M: Material> {
+    materials: ParamSet<'w, 's, (Res<'w, Assets<StandardMaterial>>, ResMut<'w, Assets<M>>)>,
+}
+
+impl<M: Material> MaterialAssets<'_, '_, M> {
+    /// the material of type `M` matching the material of the library
+    pub(crate) fn resolve(
+        &mut self,
+        library_material: &Handle<StandardMaterial>,
+        material_info: &MaterialInfo,
+        convert: Option<&MaterialConverter<M>>,
+    ) -> Option<Handle<M>> {
+        let Some(convert) = convert else {
+            // without a converter, only the materials of the library themselves can be used
+            return library_material.clone().untyped().try_typed::<M>().ok();
+        };
+        let standard_material = self.materials.p0().get(library_material)?.clone();
+        let material = convert(&standard_material, material_info)?;
+        Some(self.materials.p1().add(material))
+    }
+}
 
-pub(crate) fn inject_materials(
-    mut blenvy_config: ResMut<BlenvyMaterialConfig>,
-    material_infos_query: Query<(Entity, &MaterialInfos, &Children), Without<MaterialProcessed>>,
-    with_materials_and_meshes: Query<Entity, (With<Parent>, With<MeshMaterial3d<StandardMaterial>>, With<Mesh3d>)>,
+/// the index of the glTF primitive a mesh was spawned from, from its asset label
+fn primitive_index(mesh: &Mesh3d) -> Option<usize> {
+    label_primitive_index(mesh.0.path()?.label()?)
+}
+
+/// parses `Mesh{mesh}/Primitive{primitive}` asset labels
+fn label_primitive_index(label: &str) -> Option<usize> {
+    let (mesh_label, primitive) = label.split_once("/Primitive")?;
+    mesh_label.strip_prefix("Mesh")?.parse::<usize>().ok()?;
+    primitive.parse().ok()
+}
+
+/// the material slot (index in `MaterialInfos`) of a primitive: the slot using the primitive's material if any
+/// (unused material slots are not exported as primitives), its primitive index otherwise
+fn material_slot(
+    material_infos: &MaterialInfos,
+    material_name: Option<&GltfMaterialName>,
+    primitive_index: usize,
+) -> Option<usize> {
+    material_name
+        .and_then(|material_name| {
+            material_infos
+                .0
+                .iter()
+                .position(|material_info| material_info.name == material_name.0)
+        })
+        .or_else(|| (primitive_index < material_infos.0.len()).then_some(primitive_index))
+}
+
+/// result of looking up a material in a material library
+pub(crate) enum LibraryMaterial<'a> {
+    Found(&'a Handle<StandardMaterial>),
+    /// the material library failed to load, or does not contain the material
+    Missing,
+    /// the material library is still loading (or reloading)
+    Loading,
+}
+
+pub(crate) fn library_material<'a>(
+    library: &Handle<Gltf>,
+    material_info: &MaterialInfo,
+    assets_gltf: &'a Assets<Gltf>,
+    asset_server: &AssetServer,
+) -> LibraryMaterial<'a> {
+    match assets_gltf.get(library.id()) {
+        Some(mat_gltf) => match mat_gltf.named_materials.get(material_info.name.as_str()) {
+            Some(material) => LibraryMaterial::Found(material),
+            None => {
+                warn!("Material {} not found in GLTF {}", material_info.name, material_info.path);
+                LibraryMaterial::Missing
+            }
+        },
+        None if asset_server.load_state(library.id()).is_failed() => {
+            warn!("Material library {} failed to load", material_info.path);
+            LibraryMaterial::Missing
+        }
+        None => {
+            debug!("Material library {} not loaded yet, retrying", material_info.path);
+            LibraryMaterial::Loading
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn inject_materials<M: Material>(
+    mut blenvy_config: ResMut<BlenvyMaterialConfig<M>>,
+    material_infos_query: Query<
+        (Entity, &MaterialInfos, &Children, Option<&OriginalChildren>, Option<&Name>),
+        Without<MaterialProcessed<M>>,
+    >,
+    with_materials_and_meshes: Query<
+        (&Mesh3d, Option<&GltfMaterialName>, Has<VariantBaseMaterial<M>>, Has<MaterialInjected>),
+        With<Parent>,
+    >,
+    parents: Query<&Parent>,
+    mut variants: Query<&mut MaterialVariant>,
     assets_gltf: Res<Assets<Gltf>>,
     asset_server: Res<AssetServer>,
+    mut material_assets: MaterialAssets<M>,
     mut commands: Commands,
 ) {
-    for (entity, material_infos, children) in material_infos_query.iter() {
-        for (material_index, material_info) in material_infos.0.iter().enumerate() {
-            let material_full_path = format!("{}#{}", material_info.path, material_info.name);
-            let material_found = if let Some(material) = blenvy_config.materials_cache.get(&material_full_path) {
+    let is_standard_material = std::any::TypeId::of::<M>() == std::any::TypeId::of::<StandardMaterial>();
+    for (entity, material_infos, children, original_children, name) in material_infos_query.iter() {
+        // material libraries are preloaded as blueprint assets, but they might still be loading (or reloading)
+        let mut pending = false;
+        let mut used_slots = vec![false; material_infos.0.len()];
+        let mut missing_slots = vec![];
+        // only the primitives of the gltf mesh, not sub objects, lights, or children the instance had before spawning the blueprint
+        let primitives = children
+            .iter()
+            .filter(|child| !original_children.is_some_and(|original| original.0.contains(child)))
+            .filter_map(|child| {
+                let (mesh, material_name, with_variant, injected) = with_materials_and_meshes.get(*child).ok()?;
+                Some((*child, primitive_index(mesh)?, material_name, with_variant, injected))
+            });
+
+        for (child, primitive_index, material_name, with_variant, injected) in primitives {
+            let Some(material_index) = material_slot(material_infos, material_name, primitive_index) else {
+                missing_slots.push((primitive_index, material_name));
+                continue;
+            };
+            used_slots[material_index] = true;
+            // already injected by another material type, or by a previous pass while some libraries were still loading
+            if injected {
+                continue;
+            }
+            let material_info = &material_infos.0[material_index];
+            let library: Handle<Gltf> = asset_server.load(material_info.path.clone());
+            let cache_key = (library.id(), material_info.name.clone());
+            let material_found = if let Some(material) = blenvy_config.materials_cache.get(&cache_key) {
                 debug!("Material is cached, retrieving");
                 Some(material.clone())
             } else {
-                let model_handle: Handle<Gltf> = asset_server.load(material_info.path.clone());
-                match assets_gltf.get(model_handle.id()) {
-                    Some(mat_gltf) => match mat_gltf.named_materials.get(material_info.name.as_str()) {
-                        Some(material) => {
-                            blenvy_config.materials_cache.insert(material_full_path, material.clone());
-                            Some(material.clone())
-                        }
-                        None => {
-                            warn!("Material {} not found in GLTF {}", material_info.name, material_info.path);
-                            None
+                match library_material(&library, material_info, &assets_gltf, &asset_server) {
+                    LibraryMaterial::Found(library_material) => {
+                        let material =
+                            material_assets.resolve(library_material, material_info, blenvy_config.convert.as_ref());
+                        if let Some(material) = material.as_ref() {
+                            blenvy_config.materials_cache.insert(cache_key, material.clone());
                         }
-                    },
-                    None => {
-                        warn!("GLTF asset {} not loaded; ensure preloading", material_info.path);
+                        material
+                    }
+                    LibraryMaterial::Missing => None,
+                    LibraryMaterial::Loading => {
+                        pending = true;
                         None
                     }
                 }
             };
 
             if let Some(material) = material_found {
-                info!("Injecting/replacing materials");
-                for (child_index, child) in children.iter().enumerate() {
-                    if child_index == material_index && with_materials_and_meshes.contains(*child) {
-                        info!("Injecting material {}, path: {:?}", material_info.name, material_info.path);
-                        // Updated line: Replace MeshMaterial3d<StandardMaterial> with the new material handle
-                        commands.entity(*child).insert(MeshMaterial3d(material.clone()));
+                info!("Injecting material {}, path: {:?}", material_info.name, material_info.path);
+                let mut child_commands = commands.entity(child);
+                if !is_standard_material {
+                    child_commands.remove::<MeshMaterial3d<StandardMaterial>>();
+                }
+                child_commands.insert((MeshMaterial3d(material), MaterialInjected));
+                // re-injected (ie its material library changed) while a variant was applied:
+                // the new material is the base for the variant, which gets applied again
+                if with_variant {
+                    child_commands.remove::<VariantBaseMaterial<M>>();
+                    if let Some(instance) = variant_instance(child, &parents, |entity| variants.contains(entity)) {
+                        if let Ok(mut variant) = variants.get_mut(instance) {
+                            variant.set_changed();
+                        }
                     }
                 }
             }
         }
-        commands.entity(entity).insert(MaterialProcessed);
+
+        if pending {
+            continue;
+        }
+        if is_standard_material {
+            for (primitive_index, material_name) in missing_slots {
+                warn!(
+                    "No material slot for primitive {} (material {:?}) of {:?}: only {} material infos",
+                    primitive_index,
+                    material_name.map(|material_name| material_name.0.as_str()),
+                    name,
+                    material_infos.0.len()
+                );
+            }
+            for (material_info, _) in material_infos.0.iter().zip(used_slots).filter(|(_, used)| !used) {
+                warn!(
+                    "Material slot {} ({}) of {:?} is not used by any primitive",
+                    material_info.name, material_info.path, name
+                );
+            }
+        }
+        commands.entity(entity).insert(MaterialProcessed::<M>(PhantomData));
+    }
+}
+
+/// invalidates the cached materials of modified material libraries & re-injects them into the entities using them
+pub(crate) fn react_to_material_library_changes<M: Material>(
+    mut gltf_events: EventReader<AssetEvent<Gltf>>,
+    mut blenvy_config: ResMut<BlenvyMaterialConfig<M>>,
+    processed: Query<(Entity, &MaterialInfos, &Children), With<MaterialProcessed<M>>>,
+    mut variants: Query<(&MaterialVariants, &mut MaterialVariant)>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    let modified: Vec<AssetId<Gltf>> = gltf_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    if modified.is_empty() {
+        return;
     }
-}
\ No newline at end of file
+    blenvy_config
+        .materials_cache
+        .retain(|(library, _), _| !modified.contains(library));
+
+    let modified_paths: Vec<String> = modified
+        .iter()
+        .filter_map(|id| asset_server.get_path(*id))
+        .map(|path| path.to_string())
+        .collect();
+    for (entity, material_infos, children) in processed.iter() {
+        if material_infos
+            .0
+            .iter()
+            .any(|material_info| modified_paths.contains(&material_info.path))
+        {
+            debug!("Material library changed, re-injecting materials into {:?}", entity);
+            commands.entity(entity).remove::<MaterialProcessed<M>>();
+            for child in children.iter() {
+                commands.entity(*child).remove::<MaterialInjected>();
+            }
+        }
+    }
+
+    // the materials of active variants coming from the modified libraries
+    for (material_variants, mut variant) in variants.iter_mut() {
+        if material_variants.0.get(&variant.0).is_some_and(|replacements| {
+            replacements
+                .values()
+                .any(|material_info| modified_paths.contains(&material_info.path))
+        }) {
+            variant.set_changed();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn primitive_labels() {
+        assert_eq!(label_primitive_index("Mesh0/Primitive0"), Some(0));
+        assert_eq!(label_primitive_index("Mesh12/Primitive3"), Some(3));
+        for label in [
+            "Mesh0",
+            "Mesh0/Primitive",
+            "Mesh0/Primitive-1",
+            "Mesh0/PrimitiveA",
+            "Mesh/Primitive0",
+            "MeshA/Primitive0",
+            "Scene0/Primitive0",
+            "Mesh0/Primitive0/MorphTargets",
+            "Material0",
+            "",
+        ] {
+            assert_eq!(label_primitive_index(label), None, "{label:?}");
+        }
+    }
+
+    #[test]
+    fn material_slots() {
+        let material_info = |name: &str| MaterialInfo {
+            path: "materials/Library.glb".into(),
+            name: name.into(),
+        };
+        let material_infos = MaterialInfos(vec![material_info("Metal"), material_info("Glass")]);
+        let material_name = |name: &str| GltfMaterialName(name.into());
+
+        // the slot using the material of the primitive, whatever its index
+        assert_eq!(material_slot(&material_infos, Some(&material_name("Glass")), 0), Some(1));
+        assert_eq!(material_slot(&material_infos, Some(&material_name("Metal")), 5), Some(0));
+        // the primitive index otherwise
+        assert_eq!(material_slot(&material_infos, None, 1), Some(1));
+        assert_eq!(material_slot(&material_infos, Some(&material_name("Wood")), 0), Some(0));
+        assert_eq!(material_slot(&material_infos, Some(&material_name("Wood")), 2), None);
+        assert_eq!(material_slot(&MaterialInfos(vec![]), None, 0), None);
+    }
+}
@@ -5,6 +5,21 @@ pub use spawn_from_blueprints::*;
 pub mod animation;
 pub use animation::*;
 
+pub mod animation_control;
+pub use animation_control::*;
+
+pub mod animation_markers;
+pub use animation_markers::*;
+
+pub mod animation_graph;
+pub use animation_graph::*;
+
+pub mod animation_state_machine;
+pub use animation_state_machine::*;
+
+pub mod root_motion;
+pub use root_motion::*;
+
 pub mod aabb;
 pub use aabb::*;
 
@@ -14,6 +29,12 @@ pub use assets::*;
 pub mod materials;
 pub use materials::*;
 
+pub mod material_variants;
+pub use material_variants::*;
+
+pub mod spatial_index;
+pub use spatial_index::*;
+
 pub mod copy_components;
 pub use copy_components::*;
 
@@ -22,7 +43,7 @@ pub(crate) use hot_reload::*;
 
 use bevy::{prelude::*, utils::hashbrown::HashMap};
 
-use crate::GltfComponentsSet;
+use crate::{BlenvyRegistrationApp, GltfComponentsSet};
 
 #[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
 /// set for the two stages of blueprint based spawning :
@@ -80,30 +101,27 @@ const ASSET_ERROR: &str = ""; // TODO
 impl Plugin for BlueprintsPlugin {
     fn build(&self, app: &mut App) {
         app.register_watching_for_changes()
+            .init_resource::<BlueprintAabbCache>()
+            .init_resource::<BlueprintAnimationGraphCache>()
             .insert_resource(AssetToBlueprintInstancesMapper {
                 untyped_id_to_blueprint_entity_ids: HashMap::new(),
             })
             .add_event::<BlueprintEvent>()
-            .register_type::<BlueprintInfo>()
-            .register_type::<MaterialInfo>()
-            .register_type::<MaterialInfos>()
-            .register_type::<SpawnBlueprint>()
+            // types authored on the Blender side (deriving `BlenvyComponent`): their field types get registered automatically
+            .register_derived_blenvy_types()
+            // generic types can not derive `BlenvyComponent`
+            .register_blenvy_type::<HashMap<String, Vec<String>>>()
+            // runtime only types
             .register_type::<BlueprintInstanceDisabled>()
-            .register_type::<HideUntilReady>()
+            .register_type::<BlueprintBoundingSphere>()
+            .register_type::<BlueprintObb>()
             .register_type::<BlueprintAnimations>()
             .register_type::<InstanceAnimations>()
-            .register_type::<AnimationInfo>()
-            .register_type::<AnimationInfos>()
-            .register_type::<Vec<AnimationInfo>>()
-            .register_type::<AnimationMarkers>()
-            .register_type::<HashMap<u32, Vec<String>>>()
-            .register_type::<HashMap<String, HashMap<u32, Vec<String>>>>()
+            .register_type::<CurrentAnimationState>()
+            .register_type::<RootMotionDelta>()
             .add_event::<AnimationMarkerReached>()
-            .register_type::<BlueprintAsset>()
-            .register_type::<Vec<BlueprintAsset>>()
-            .register_type::<Vec<String>>()
-            .register_type::<BlueprintAssets>()
-            .register_type::<HashMap<String, Vec<String>>>()
+            .init_resource::<AnimationMarkerClips>()
+            .init_resource::<BlenvyMaterialConfig>()
             //grok says add id_test if it"s not there, sadly we dont trust grok so we havent done that
             //.register_type::<Id_test>()
             .add_plugins(RonAssetPlugin::<BlueprintPreloadAssets>::new(&["meta.ron"]))
@@ -127,22 +145,43 @@ impl Plugin for BlueprintsPlugin {
                     blueprints_scenes_spawned,
                     blueprints_cleanup_spawned_scene,
                     // beyond this point : post processing to finalize blueprint instances
-                    inject_materials,
+                    inject_materials::<StandardMaterial>,
                     compute_scene_aabbs,
                     blueprints_finalize_instances,
                 )
                     .chain()
                     .in_set(GltfBlueprintsSet::Spawn),
             )
-            // animation
             .add_systems(
                 Update,
                 (
-                    trigger_blueprint_animation_markers_events,
-                    trigger_instance_animation_markers_events,
-                ),
+                    apply_material_variants::<StandardMaterial>,
+                    remove_material_variants::<StandardMaterial>,
+                    recompute_dynamic_aabbs,
+                    update_animation_state_machines,
+                    setup_root_motion,
+                )
+                    .in_set(GltfBlueprintsSet::AfterSpawn),
+            )
+            // animation
+            .add_observer(forward_animation_marker_events)
+            .add_systems(
+                PostUpdate,
+                extract_root_motion
+                    .after(bevy::app::Animation)
+                    .before(TransformSystem::TransformPropagate),
             )
             // hot reload
-            .add_systems(Update, react_to_asset_changes.run_if(hot_reload));
+            .add_systems(
+                Update,
+                (
+                    react_to_asset_changes,
+                    react_to_material_library_changes::<StandardMaterial>,
+                    invalidate_blueprint_aabbs,
+                    invalidate_blueprint_animation_graphs,
+                    invalidate_animation_marker_clips,
+                )
+                    .run_if(hot_reload),
+            );
     }
 }
@@ -0,0 +1,292 @@
+use bevy::{animation::AnimationTarget, math::Affine3A, prelude::*};
+
+use crate::BlueprintAnimationPlayerLink;
+use crate::BlenvyComponent;
+
+/// Add this to a blueprint (in Blender) or a blueprint instance to extract the motion of the root bone from its animations:
+/// the root bone stays in place in the pose, & its motion is available every frame in [`RootMotionDelta`]
+#[derive(Component, Reflect, Default, Debug, Clone, BlenvyComponent)]
+#[reflect(Component)]
+pub struct RootMotion {
+    /// the name of the root bone, the first animated descendant of the armature if none
+    #[reflect(default)]
+    pub root_bone: Option<String>,
+    /// also extract the rotation of the root bone around the vertical axis
+    #[reflect(default)]
+    pub extract_rotation: bool,
+    /// keep the vertical part of the root bone's translation in the pose (ie for bobbing), instead of extracting it
+    #[reflect(default)]
+    pub keep_vertical: bool,
+    /// apply the motion to the `Transform` of the instance, instead of only exposing it in `RootMotionDelta`
+    #[reflect(default)]
+    pub apply_to_transform: bool,
+}
+
+/// The motion of the root bone during the last frame, in the local space of the blueprint instance
+#[derive(Component, Reflect, Debug, Clone, Copy, PartialEq)]
+#[reflect(Component)]
+pub struct RootMotionDelta {
+    pub translation: Vec3,
+    pub rotation: Quat,
+}
+
+impl Default for RootMotionDelta {
+    fn default() -> Self {
+        RootMotionDelta {
+            translation: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub(crate) struct RootMotionState {
+    bone: Entity,
+    /// the transform of the root bone when the instance was spawned
+    rest: Transform,
+    /// the motion extracted from the pose, relative to the rest transform
+    extracted_translation: Vec3,
+    extracted_rotation: Quat,
+    /// the last delta & the time it took, to carry on the motion when the pose jumps (loops, restarts)
+    delta: RootMotionDelta,
+    delta_seconds: f32,
+    /// index, seek time, speed & completions of the animations played during the last frame
+    playback: Vec<(AnimationNodeIndex, f32, f32, u32)>,
+}
+
+/// finds the root bones of the instances with [`RootMotion`], once they are spawned
+pub(crate) fn setup_root_motion(
+    instances: Query<(Entity, &RootMotion, &BlueprintAnimationPlayerLink), Without<RootMotionState>>,
+    bones: Query<(&Name, &Transform), With<AnimationTarget>>,
+    all_children: Query<&Children>,
+    mut commands: Commands,
+) {
+    for (entity, root_motion, player_link) in instances.iter() {
+        // breadth first, so the first animated descendant is the root bone
+        let bone = all_children.iter_descendants(player_link.0).find(|bone| {
+            bones.get(*bone).is_ok_and(|(name, _)| {
+                root_motion
+                    .root_bone
+                    .as_ref()
+                    .is_none_or(|root_bone| root_bone == name.as_str())
+            })
+        });
+        let Some((bone, (_, rest))) = bone.and_then(|bone| bones.get(bone).ok().map(|found| (bone, found))) else {
+            debug!("no root bone found for root motion of {:?}", entity);
+            continue;
+        };
+        commands.entity(entity).insert((
+            RootMotionState {
+                bone,
+                rest: *rest,
+                extracted_translation: Vec3::ZERO,
+                extracted_rotation: Quat::IDENTITY,
+                delta: RootMotionDelta::default(),
+                delta_seconds: 0.0,
+                playback: vec![],
+            },
+            RootMotionDelta::default(),
+        ));
+    }
+}
+
+/// runs after the animations have been applied: moves the root bones back to their rest transform & computes the root motion deltas
+#[allow(clippy::type_complexity)]
+pub(crate) fn extract_root_motion(
+    mut instances: Query<(
+        Entity,
+        &RootMotion,
+        &BlueprintAnimationPlayerLink,
+        &mut RootMotionState,
+        &mut RootMotionDelta,
+    )>,
+    players: Query<&AnimationPlayer>,
+    mut transforms: Query<&mut Transform>,
+    parents: Query<&Parent>,
+    time: Res<Time>,
+) {
+    for (entity, root_motion, player_link, mut state, mut root_motion_delta) in instances.iter_mut() {
+        let Ok(player) = players.get(player_link.0) else {
+            continue;
+        };
+        let playback: Vec<(AnimationNodeIndex, f32, f32, u32)> = player
+            .playing_animations()
+            .map(|(index, animation)| (*index, animation.seek_time(), animation.speed(), animation.completions()))
+            .collect();
+        if playback.is_empty() {
+            // nothing poses the root bone anymore
+            state.extracted_translation = Vec3::ZERO;
+            state.extracted_rotation = Quat::IDENTITY;
+            state.playback.clear();
+            *root_motion_delta = RootMotionDelta::default();
+            continue;
+        }
+        // looped, restarted or newly started animations make the pose jump
+        let discontinuity = state.playback.is_empty()
+            || playback.iter().any(|(index, seek_time, speed, completions)| {
+                state.playback.iter().any(|(previous_index, previous_seek_time, _, previous_completions)| {
+                    previous_index == index
+                        && (previous_completions != completions || (seek_time - previous_seek_time) * speed.signum() < 0.0)
+                })
+            });
+
+        // from the space of the root bone's parent to the space of the instance
+        let mut parent_to_instance = Affine3A::IDENTITY;
+        for ancestor in parents.iter_ancestors(state.bone) {
+            if ancestor == entity {
+                break;
+            }
+            if let Ok(transform) = transforms.get(ancestor) {
+                parent_to_instance = transform.compute_affine() * parent_to_instance;
+            }
+        }
+        let (_, parent_rotation, _) = parent_to_instance.to_scale_rotation_translation();
+
+        let Ok(mut bone) = transforms.get_mut(state.bone) else {
+            continue;
+        };
+        let mut extracted_translation = parent_to_instance.transform_vector3(bone.translation - state.rest.translation);
+        if root_motion.keep_vertical {
+            extracted_translation.y = 0.0;
+        }
+        bone.translation -= parent_to_instance.inverse().transform_vector3(extracted_translation);
+
+        let extracted_rotation = if root_motion.extract_rotation {
+            // the rotation relative to the rest one, around the vertical axis of the instance
+            let rotation = parent_rotation * bone.rotation * (parent_rotation * state.rest.rotation).inverse();
+            let twist = Quat::from_xyzw(0.0, rotation.y, 0.0, rotation.w);
+            if twist.length_squared() > f32::EPSILON {
+                let twist = twist.normalize();
+                bone.rotation = parent_rotation.inverse() * twist.inverse() * parent_rotation * bone.rotation;
+                twist
+            } else {
+                Quat::IDENTITY
+            }
+        } else {
+            Quat::IDENTITY
+        };
+
+        let delta = if discontinuity {
+            let ratio = if state.delta_seconds > 0.0 {
+                time.delta_secs() / state.delta_seconds
+            } else {
+                0.0
+            };
+            RootMotionDelta {
+                translation: state.delta.translation * ratio,
+                rotation: Quat::IDENTITY.slerp(state.delta.rotation, ratio),
+            }
+        } else {
+            RootMotionDelta {
+                translation: extracted_translation - state.extracted_translation,
+                rotation: extracted_rotation * state.extracted_rotation.inverse(),
+            }
+        };
+        state.extracted_translation = extracted_translation;
+        state.extracted_rotation = extracted_rotation;
+        state.delta = delta;
+        state.delta_seconds = time.delta_secs();
+        state.playback = playback;
+        *root_motion_delta = delta;
+
+        if root_motion.apply_to_transform {
+            if let Ok(mut transform) = transforms.get_mut(entity) {
+                let translation = transform.rotation * (transform.scale * delta.translation);
+                transform.translation += translation;
+                transform.rotation *= delta.rotation;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{animation::AnimationTargetId, ecs::system::RunSystemOnce};
+
+    use super::*;
+
+    /// an instance moved by the root motion, with its animation player & root bone
+    fn spawn_instance(world: &mut World) -> (Entity, Entity, Entity) {
+        let instance = world.spawn(Transform::default()).id();
+        let player = world.spawn((AnimationPlayer::default(), Transform::default())).id();
+        let bone = world
+            .spawn((
+                Name::new("Root"),
+                AnimationTarget {
+                    id: AnimationTargetId::from_name(&Name::new("Root")),
+                    player,
+                },
+                Transform::default(),
+            ))
+            .id();
+        world.entity_mut(instance).add_child(player);
+        world.entity_mut(player).add_child(bone);
+        world.entity_mut(instance).insert((
+            RootMotion {
+                apply_to_transform: true,
+                ..default()
+            },
+            BlueprintAnimationPlayerLink(player),
+        ));
+        world.run_system_once(setup_root_motion).unwrap();
+        (instance, player, bone)
+    }
+
+    /// poses the root bone like the animation would at `seek_time`, then extracts the root motion
+    fn animate(world: &mut World, player: Entity, bone: Entity, seek_time: f32, bone_translation: Vec3) -> RootMotionDelta {
+        let animation = AnimationNodeIndex::new(1);
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_millis(100));
+        let mut animation_player = world.get_mut::<AnimationPlayer>(player).unwrap();
+        if !animation_player.is_playing_animation(animation) {
+            animation_player.start(animation);
+        }
+        animation_player.animation_mut(animation).unwrap().seek_to(seek_time);
+        world.get_mut::<Transform>(bone).unwrap().translation = bone_translation;
+        world.run_system_once(extract_root_motion).unwrap();
+        let instance = world.get::<Parent>(player).unwrap().get();
+        *world.get::<RootMotionDelta>(instance).unwrap()
+    }
+
+    #[test]
+    fn root_bone_motion_moves_the_instance() {
+        let mut world = World::new();
+        world.insert_resource(Time::<()>::default());
+        let (instance, player, bone) = spawn_instance(&mut world);
+
+        // the first pose has no previous one to compute a delta from
+        let delta = animate(&mut world, player, bone, 0.0, Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(delta, RootMotionDelta::default());
+        assert_eq!(world.get::<Transform>(bone).unwrap().translation, Vec3::ZERO);
+
+        let delta = animate(&mut world, player, bone, 0.1, Vec3::new(0.0, 0.0, 1.5));
+        assert!(delta.translation.abs_diff_eq(Vec3::new(0.0, 0.0, 0.5), 1e-5));
+        // the root bone stays in place, the instance moves instead
+        assert_eq!(world.get::<Transform>(bone).unwrap().translation, Vec3::ZERO);
+        let translation = world.get::<Transform>(instance).unwrap().translation;
+        assert!(translation.abs_diff_eq(Vec3::new(0.0, 0.0, 0.5), 1e-5));
+    }
+
+    #[test]
+    fn looping_clips_carry_on_the_motion() {
+        let mut world = World::new();
+        world.insert_resource(Time::<()>::default());
+        let (instance, player, bone) = spawn_instance(&mut world);
+
+        animate(&mut world, player, bone, 0.0, Vec3::new(0.0, 0.0, 1.0));
+        animate(&mut world, player, bone, 0.1, Vec3::new(0.0, 0.0, 1.5));
+        // the clip loops: the root bone jumps back, the instance keeps on moving at the same speed
+        let delta = animate(&mut world, player, bone, 0.05, Vec3::new(0.0, 0.0, 0.2));
+        assert!(delta.translation.abs_diff_eq(Vec3::new(0.0, 0.0, 0.5), 1e-5));
+        // then follows the root bone again
+        let delta = animate(&mut world, player, bone, 0.15, Vec3::new(0.0, 0.0, 0.5));
+        assert!(delta.translation.abs_diff_eq(Vec3::new(0.0, 0.0, 0.3), 1e-5));
+
+        let translation = world.get::<Transform>(instance).unwrap().translation;
+        assert!(translation.abs_diff_eq(Vec3::new(0.0, 0.0, 1.3), 1e-5));
+        assert_eq!(world.get::<Transform>(bone).unwrap().translation, Vec3::ZERO);
+    }
+}
@@ -0,0 +1,641 @@
+use bevy::{
+    math::{Affine3A, Vec3A},
+    prelude::*,
+    render::primitives::{Aabb, Frustum},
+    transform::TransformSystem,
+    utils::{HashMap, HashSet},
+};
+
+use crate::BlueprintInstanceReady;
+
+/// entries spanning more cells than this are not stored in the grid, but tested by every query
+const MAX_CELLS_PER_ENTRY: i32 = 64;
+
+/// Optional plugin maintaining a [`BlueprintSpatialIndex`] of the world space bounds of all blueprint instances
+/// (with [`BlueprintInstanceReady`] & an `Aabb`)
+#[derive(Debug, Clone)]
+pub struct BlueprintSpatialIndexPlugin {
+    /// size of the cells of the grid, in world units: ideally a bit larger than most blueprint instances
+    pub cell_size: f32,
+}
+
+impl Default for BlueprintSpatialIndexPlugin {
+    fn default() -> Self {
+        Self { cell_size: 10.0 }
+    }
+}
+
+impl Plugin for BlueprintSpatialIndexPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(BlueprintSpatialIndex::new(self.cell_size))
+            .add_systems(
+                PostUpdate,
+                update_blueprint_spatial_index.after(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// An indexed blueprint instance
+#[derive(Debug, Clone, Copy)]
+pub struct SpatialIndexEntry {
+    /// the bounds of the instance in its local space
+    pub aabb: Aabb,
+    pub world_from_local: Affine3A,
+    /// the axis aligned bounds of the instance in world space
+    pub world_aabb: Aabb,
+    /// range of cells of the entry, `None` for entries too large for the grid
+    cells: Option<(IVec3, IVec3)>,
+}
+
+impl SpatialIndexEntry {
+    fn new(aabb: Aabb, world_from_local: Affine3A) -> Self {
+        let matrix = world_from_local.matrix3;
+        let half_extents = matrix.x_axis.abs() * aabb.half_extents.x
+            + matrix.y_axis.abs() * aabb.half_extents.y
+            + matrix.z_axis.abs() * aabb.half_extents.z;
+        Self {
+            aabb,
+            world_from_local,
+            world_aabb: Aabb {
+                center: world_from_local.transform_point3a(aabb.center),
+                half_extents,
+            },
+            cells: None,
+        }
+    }
+
+    /// distance along the ray to the (oriented) bounds of the entry, 0 if the ray starts inside of them
+    pub fn ray_distance(&self, ray: Ray3d) -> Option<f32> {
+        let local_from_world = self.world_from_local.inverse();
+        let origin = local_from_world.transform_point3a(Vec3A::from(ray.origin));
+        // not normalized: distances along the local ray are the same as along the world ray
+        let direction = local_from_world.transform_vector3a(Vec3A::from(*ray.direction));
+        ray_aabb_distance(origin, direction, self.aabb.min(), self.aabb.max())
+    }
+
+    /// distance from a point to the world space bounds of the entry, 0 if the point is inside of them
+    pub fn distance_to_point(&self, point: Vec3) -> f32 {
+        ((Vec3A::from(point) - self.world_aabb.center).abs() - self.world_aabb.half_extents)
+            .max(Vec3A::ZERO)
+            .length()
+    }
+
+    fn intersects_aabb(&self, min: Vec3A, max: Vec3A) -> bool {
+        self.world_aabb.min().cmple(max).all() && self.world_aabb.max().cmpge(min).all()
+    }
+}
+
+/// Spatial index (uniform grid) over the world space bounds of blueprint instances, maintained by [`BlueprintSpatialIndexPlugin`]:
+/// for placement validation, picking etc without a physics engine.
+/// Box, sphere & nearest queries use the world space axis aligned bounds, ray casts & frustum queries the oriented bounds
+#[derive(Resource, Debug)]
+pub struct BlueprintSpatialIndex {
+    cell_size: f32,
+    cells: HashMap<IVec3, Vec<Entity>>,
+    entries: HashMap<Entity, SpatialIndexEntry>,
+    oversized: HashSet<Entity>,
+    /// range of all the cells ever used (it never shrinks)
+    grid_bounds: Option<(IVec3, IVec3)>,
+}
+
+impl BlueprintSpatialIndex {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size: cell_size.max(f32::EPSILON),
+            cells: HashMap::new(),
+            entries: HashMap::new(),
+            oversized: HashSet::new(),
+            grid_bounds: None,
+        }
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<&SpatialIndexEntry> {
+        self.entries.get(&entity)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// adds or updates an entity with its local bounds & transform
+    pub fn insert(&mut self, entity: Entity, aabb: Aabb, world_from_local: Affine3A) {
+        self.remove(entity);
+        let mut entry = SpatialIndexEntry::new(aabb, world_from_local);
+        let (min, max) = (
+            self.cell_of(entry.world_aabb.min()),
+            self.cell_of(entry.world_aabb.max()),
+        );
+        let size = max - min + IVec3::ONE;
+        if size.x.saturating_mul(size.y).saturating_mul(size.z) > MAX_CELLS_PER_ENTRY {
+            self.oversized.insert(entity);
+        } else {
+            for cell in cell_range(min, max) {
+                self.cells.entry(cell).or_default().push(entity);
+            }
+            entry.cells = Some((min, max));
+            self.grid_bounds = Some(match self.grid_bounds {
+                Some((grid_min, grid_max)) => (grid_min.min(min), grid_max.max(max)),
+                None => (min, max),
+            });
+        }
+        self.entries.insert(entity, entry);
+    }
+
+    pub fn remove(&mut self, entity: Entity) -> Option<SpatialIndexEntry> {
+        let entry = self.entries.remove(&entity)?;
+        match entry.cells {
+            Some((min, max)) => {
+                for cell in cell_range(min, max) {
+                    if let Some(entities) = self.cells.get_mut(&cell) {
+                        entities.retain(|other| *other != entity);
+                        if entities.is_empty() {
+                            self.cells.remove(&cell);
+                        }
+                    }
+                }
+            }
+            None => {
+                self.oversized.remove(&entity);
+            }
+        }
+        Some(entry)
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.entries.clear();
+        self.oversized.clear();
+        self.grid_bounds = None;
+    }
+
+    /// the instances whose bounds intersect the given world space box
+    pub fn entities_in_aabb(&self, min: Vec3, max: Vec3) -> Vec<Entity> {
+        let (min, max) = (Vec3A::from(min), Vec3A::from(max));
+        self.candidates(self.cell_of(min), self.cell_of(max))
+            .into_iter()
+            .filter(|entity| self.entries[entity].intersects_aabb(min, max))
+            .collect()
+    }
+
+    /// the instances whose bounds intersect the given world space sphere
+    pub fn entities_in_sphere(&self, center: Vec3, radius: f32) -> Vec<Entity> {
+        let extents = Vec3A::splat(radius);
+        let (min, max) = (Vec3A::from(center) - extents, Vec3A::from(center) + extents);
+        self.candidates(self.cell_of(min), self.cell_of(max))
+            .into_iter()
+            .filter(|entity| self.entries[entity].distance_to_point(center) <= radius)
+            .collect()
+    }
+
+    /// the instances whose bounds intersect the given frustum, ie that of a camera
+    pub fn entities_in_frustum(&self, frustum: &Frustum) -> Vec<Entity> {
+        let cell_extents = Vec3A::splat(self.cell_size / 2.0);
+        let mut found = HashSet::new();
+        let cells = self
+            .cells
+            .iter()
+            .filter(|(cell, _)| {
+                let cell_aabb = Aabb {
+                    center: (cell.as_vec3a() + Vec3A::splat(0.5)) * self.cell_size,
+                    half_extents: cell_extents,
+                };
+                frustum.intersects_obb(&cell_aabb, &Affine3A::IDENTITY, true, true)
+            })
+            .flat_map(|(_, entities)| entities.iter());
+        for entity in cells.chain(self.oversized.iter()) {
+            if found.contains(entity) {
+                continue;
+            }
+            let entry = &self.entries[entity];
+            if frustum.intersects_obb(&entry.aabb, &entry.world_from_local, true, true) {
+                found.insert(*entity);
+            }
+        }
+        found.into_iter().collect()
+    }
+
+    /// the closest instance hit by the ray (within `max_distance`) & its distance, for which `filter` returns true
+    pub fn raycast(&self, ray: Ray3d, max_distance: f32, filter: impl Fn(Entity) -> bool) -> Option<(Entity, f32)> {
+        let mut tested = HashSet::new();
+        let mut closest: Option<(Entity, f32)> = None;
+        let mut test = |entity: Entity, closest: &mut Option<(Entity, f32)>| {
+            if !tested.insert(entity) || !filter(entity) {
+                return;
+            }
+            let Some(distance) = self.entries[&entity].ray_distance(ray) else {
+                return;
+            };
+            if distance <= max_distance && closest.is_none_or(|(_, closest)| distance < closest) {
+                *closest = Some((entity, distance));
+            }
+        };
+        for entity in self.oversized.iter() {
+            test(*entity, &mut closest);
+        }
+
+        // walk the cells along the ray, within the grid (3D DDA)
+        let Some((grid_min, grid_max)) = self.grid_bounds else {
+            return closest;
+        };
+        let origin = Vec3A::from(ray.origin);
+        let direction = Vec3A::from(*ray.direction);
+        let Some(start) = ray_aabb_distance(
+            origin,
+            direction,
+            grid_min.as_vec3a() * self.cell_size,
+            (grid_max + IVec3::ONE).as_vec3a() * self.cell_size,
+        ) else {
+            return closest;
+        };
+        let mut cell = self
+            .cell_of(origin + direction * start)
+            .clamp(grid_min, grid_max);
+        let mut step = IVec3::ZERO;
+        let mut next_boundary = Vec3A::splat(f32::INFINITY);
+        let mut boundary_step = Vec3A::splat(f32::INFINITY);
+        for axis in 0..3 {
+            if direction[axis] != 0.0 {
+                step[axis] = direction[axis].signum() as i32;
+                let boundary = (cell[axis] + i32::from(step[axis] > 0)) as f32 * self.cell_size;
+                next_boundary[axis] = (boundary - origin[axis]) / direction[axis];
+                boundary_step[axis] = self.cell_size / direction[axis].abs();
+            }
+        }
+        loop {
+            if let Some(entities) = self.cells.get(&cell) {
+                for entity in entities {
+                    test(*entity, &mut closest);
+                }
+            }
+            let exit = next_boundary.min_element();
+            // nothing in the next cells can be closer than the closest hit so far
+            if exit > max_distance || closest.is_some_and(|(_, closest)| closest <= exit) {
+                break;
+            }
+            let axis = if next_boundary.x == exit {
+                0
+            } else if next_boundary.y == exit {
+                1
+            } else {
+                2
+            };
+            cell[axis] += step[axis];
+            if cell[axis] < grid_min[axis] || cell[axis] > grid_max[axis] {
+                break;
+            }
+            next_boundary[axis] += boundary_step[axis];
+        }
+        closest
+    }
+
+    /// the instance closest to a point (within `max_distance`) & its distance, for which `filter` returns true
+    pub fn nearest(&self, point: Vec3, max_distance: f32, filter: impl Fn(Entity) -> bool) -> Option<(Entity, f32)> {
+        let mut tested = HashSet::new();
+        let mut closest: Option<(Entity, f32)> = None;
+        let mut test = |entity: Entity, closest: &mut Option<(Entity, f32)>| {
+            if !tested.insert(entity) || !filter(entity) {
+                return;
+            }
+            let distance = self.entries[&entity].distance_to_point(point);
+            if distance <= max_distance && closest.is_none_or(|(_, closest)| distance < closest) {
+                *closest = Some((entity, distance));
+            }
+        };
+        for entity in self.oversized.iter() {
+            test(*entity, &mut closest);
+        }
+
+        let Some((grid_min, grid_max)) = self.grid_bounds else {
+            return closest;
+        };
+        // search rings of cells of growing size around the point
+        let center = self.cell_of(Vec3A::from(point));
+        let max_ring = (center - grid_min)
+            .max(grid_max - center)
+            .max_element()
+            .min(((max_distance / self.cell_size).ceil() as i32).saturating_add(1));
+        for ring in 0..=max_ring {
+            // everything in this ring is at least this far from the point
+            let min_distance = (ring - 1).max(0) as f32 * self.cell_size;
+            if closest.is_some_and(|(_, closest)| closest <= min_distance) {
+                break;
+            }
+            let (min, max) = (
+                (center - IVec3::splat(ring)).max(grid_min),
+                (center + IVec3::splat(ring)).min(grid_max),
+            );
+            for cell in cell_range(min, max).filter(|cell| (*cell - center).abs().max_element() == ring) {
+                if let Some(entities) = self.cells.get(&cell) {
+                    for entity in entities {
+                        test(*entity, &mut closest);
+                    }
+                }
+            }
+        }
+        closest
+    }
+
+    fn cell_of(&self, point: Vec3A) -> IVec3 {
+        (point / self.cell_size).floor().as_ivec3()
+    }
+
+    /// the (deduplicated) entities of the cells in the given range & the oversized entities
+    fn candidates(&self, min: IVec3, max: IVec3) -> Vec<Entity> {
+        let size = (max - min + IVec3::ONE).as_i64vec3();
+        let mut candidates: HashSet<Entity> = self.oversized.iter().copied().collect();
+        if size.x * size.y * size.z > self.cells.len() as i64 {
+            // large queries: go through the used cells rather than through all the cells of the range
+            for (cell, entities) in self.cells.iter() {
+                if cell.cmpge(min).all() && cell.cmple(max).all() {
+                    candidates.extend(entities.iter().copied());
+                }
+            }
+        } else {
+            for cell in cell_range(min, max) {
+                if let Some(entities) = self.cells.get(&cell) {
+                    candidates.extend(entities.iter().copied());
+                }
+            }
+        }
+        candidates.into_iter().collect()
+    }
+}
+
+fn cell_range(min: IVec3, max: IVec3) -> impl Iterator<Item = IVec3> {
+    (min.x..=max.x).flat_map(move |x| (min.y..=max.y).flat_map(move |y| (min.z..=max.z).map(move |z| IVec3::new(x, y, z))))
+}
+
+/// distance along the ray (in units of `direction`) to the box, 0 if the ray starts inside of it
+fn ray_aabb_distance(origin: Vec3A, direction: Vec3A, min: Vec3A, max: Vec3A) -> Option<f32> {
+    let (mut enter, mut exit) = (0.0_f32, f32::INFINITY);
+    for axis in 0..3 {
+        if direction[axis] == 0.0 {
+            // parallel to the slab (no 0 * inf): always or never within it
+            if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                return None;
+            }
+            continue;
+        }
+        let to_min = (min[axis] - origin[axis]) / direction[axis];
+        let to_max = (max[axis] - origin[axis]) / direction[axis];
+        enter = enter.max(to_min.min(to_max));
+        exit = exit.min(to_min.max(to_max));
+    }
+    (exit >= enter).then_some(enter)
+}
+
+/// keeps the spatial index in sync with the blueprint instances
+pub(crate) fn update_blueprint_spatial_index(
+    mut spatial_index: ResMut<BlueprintSpatialIndex>,
+    changed: Query<
+        (Entity, &Aabb, &GlobalTransform),
+        (
+            With<BlueprintInstanceReady>,
+            Or<(Changed<GlobalTransform>, Changed<Aabb>, Added<BlueprintInstanceReady>)>,
+        ),
+    >,
+    mut removed_instances: RemovedComponents<BlueprintInstanceReady>,
+    mut removed_aabbs: RemovedComponents<Aabb>,
+) {
+    for entity in removed_instances.read().chain(removed_aabbs.read()) {
+        spatial_index.remove(entity);
+    }
+    for (entity, aabb, global_transform) in changed.iter() {
+        spatial_index.insert(entity, *aabb, global_transform.affine());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_4, SQRT_2};
+
+    use super::*;
+
+    fn entity(index: u32) -> Entity {
+        Entity::from_raw(index)
+    }
+
+    fn aabb(half_extents: Vec3) -> Aabb {
+        Aabb {
+            center: Vec3A::ZERO,
+            half_extents: half_extents.into(),
+        }
+    }
+
+    /// an index with cells of size 10, & boxes of the given half extents at the given positions
+    fn index_with(boxes: &[(Vec3, Vec3)]) -> BlueprintSpatialIndex {
+        let mut index = BlueprintSpatialIndex::new(10.0);
+        for (i, (position, half_extents)) in boxes.iter().enumerate() {
+            index.insert(entity(i as u32), aabb(*half_extents), Affine3A::from_translation(*position));
+        }
+        index
+    }
+
+    fn sorted(mut entities: Vec<Entity>) -> Vec<Entity> {
+        entities.sort();
+        entities
+    }
+
+    fn ray(origin: Vec3, direction: Vec3) -> Ray3d {
+        Ray3d::new(origin, Dir3::new(direction).unwrap())
+    }
+
+    fn camera_frustum() -> Frustum {
+        // at the origin, looking towards -Z, 90 degrees field of view, far plane at 100
+        let clip_from_world = Mat4::perspective_infinite_reverse_rh(std::f32::consts::FRAC_PI_2, 1.0, 0.1);
+        Frustum::from_clip_from_world_custom_far(&clip_from_world, &Vec3::ZERO, &Vec3::Z, 100.0)
+    }
+
+    #[test]
+    fn empty_index() {
+        let index = BlueprintSpatialIndex::new(10.0);
+        assert!(index.is_empty());
+        assert!(index.entities_in_aabb(Vec3::splat(-100.0), Vec3::splat(100.0)).is_empty());
+        assert!(index.entities_in_sphere(Vec3::ZERO, 100.0).is_empty());
+        assert!(index.entities_in_frustum(&camera_frustum()).is_empty());
+        assert_eq!(index.raycast(ray(Vec3::ZERO, Vec3::X), f32::MAX, |_| true), None);
+        assert_eq!(index.nearest(Vec3::ZERO, f32::MAX, |_| true), None);
+    }
+
+    #[test]
+    fn insert_update_remove() {
+        let mut index = index_with(&[(Vec3::new(5.0, 5.0, 5.0), Vec3::ONE)]);
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.entities_in_aabb(Vec3::ZERO, Vec3::splat(10.0)), vec![entity(0)]);
+
+        // moving it to another cell
+        index.insert(entity(0), aabb(Vec3::ONE), Affine3A::from_translation(Vec3::new(-25.0, 5.0, 5.0)));
+        assert_eq!(index.len(), 1);
+        assert!(index.entities_in_aabb(Vec3::ZERO, Vec3::splat(10.0)).is_empty());
+        assert_eq!(
+            index.entities_in_aabb(Vec3::new(-30.0, 0.0, 0.0), Vec3::new(-20.0, 10.0, 10.0)),
+            vec![entity(0)]
+        );
+
+        assert!(index.remove(entity(0)).is_some());
+        assert!(index.remove(entity(0)).is_none());
+        assert!(index.is_empty());
+        assert!(index.cells.is_empty());
+    }
+
+    #[test]
+    fn world_bounds_of_rotated_entries() {
+        let mut index = BlueprintSpatialIndex::new(10.0);
+        let world_from_local = Affine3A::from_rotation_translation(Quat::from_rotation_z(FRAC_PI_4), Vec3::ZERO);
+        index.insert(entity(0), aabb(Vec3::new(1.0, 1.0, 1.0)), world_from_local);
+        let world_aabb = index.get(entity(0)).unwrap().world_aabb;
+        assert!((world_aabb.half_extents - Vec3A::new(SQRT_2, SQRT_2, 1.0)).length() < 1e-5);
+    }
+
+    #[test]
+    fn aabb_queries_across_cell_boundaries() {
+        let index = index_with(&[
+            // spans cells 0 & 1 along x
+            (Vec3::new(10.0, 5.0, 5.0), Vec3::ONE),
+            // in negative cells
+            (Vec3::new(-5.0, -5.0, -5.0), Vec3::ONE),
+            // far away
+            (Vec3::new(100.0, 5.0, 5.0), Vec3::ONE),
+        ]);
+        // from each side of the cell boundary
+        assert_eq!(index.entities_in_aabb(Vec3::new(8.0, 0.0, 0.0), Vec3::new(9.5, 10.0, 10.0)), vec![entity(0)]);
+        assert_eq!(index.entities_in_aabb(Vec3::new(10.5, 0.0, 0.0), Vec3::new(12.0, 10.0, 10.0)), vec![entity(0)]);
+        // touching bounds count as intersecting
+        assert_eq!(index.entities_in_aabb(Vec3::new(11.0, 0.0, 0.0), Vec3::new(15.0, 10.0, 10.0)), vec![entity(0)]);
+        assert!(index.entities_in_aabb(Vec3::new(11.1, 0.0, 0.0), Vec3::new(15.0, 10.0, 10.0)).is_empty());
+        assert_eq!(index.entities_in_aabb(Vec3::splat(-10.0), Vec3::splat(-4.0)), vec![entity(1)]);
+        // large queries go through the used cells
+        assert_eq!(
+            sorted(index.entities_in_aabb(Vec3::splat(-1000.0), Vec3::splat(1000.0))),
+            vec![entity(0), entity(1), entity(2)]
+        );
+    }
+
+    #[test]
+    fn oversized_entries() {
+        let index = index_with(&[(Vec3::ZERO, Vec3::splat(100.0)), (Vec3::new(5.0, 5.0, 5.0), Vec3::ONE)]);
+        assert!(index.oversized.contains(&entity(0)));
+        assert_eq!(
+            sorted(index.entities_in_aabb(Vec3::new(4.0, 4.0, 4.0), Vec3::new(6.0, 6.0, 6.0))),
+            vec![entity(0), entity(1)]
+        );
+        assert_eq!(index.entities_in_aabb(Vec3::splat(90.0), Vec3::splat(95.0)), vec![entity(0)]);
+        assert_eq!(
+            index.raycast(ray(Vec3::new(500.0, 0.0, 0.0), -Vec3::X), f32::MAX, |_| true),
+            Some((entity(0), 400.0))
+        );
+        assert_eq!(index.nearest(Vec3::new(150.0, 0.0, 0.0), f32::MAX, |_| true), Some((entity(0), 50.0)));
+    }
+
+    #[test]
+    fn sphere_queries() {
+        let index = index_with(&[(Vec3::new(10.0, 0.0, 0.0), Vec3::ONE), (Vec3::new(0.0, 20.0, 0.0), Vec3::ONE)]);
+        assert!(index.entities_in_sphere(Vec3::ZERO, 8.9).is_empty());
+        assert_eq!(index.entities_in_sphere(Vec3::ZERO, 9.0), vec![entity(0)]);
+        assert_eq!(sorted(index.entities_in_sphere(Vec3::ZERO, 19.0)), vec![entity(0), entity(1)]);
+        // the bounds of the sphere overlap the box, but not the sphere itself: its corner is further than its faces
+        assert!(index.entities_in_sphere(Vec3::new(5.0, 5.0, 0.0), 5.5).is_empty());
+        assert_eq!(index.entities_in_sphere(Vec3::new(5.0, 5.0, 0.0), 5.7), vec![entity(0)]);
+    }
+
+    #[test]
+    fn ray_aabb_distances() {
+        let (min, max) = (Vec3A::splat(-1.0), Vec3A::splat(1.0));
+        assert_eq!(ray_aabb_distance(Vec3A::new(-5.0, 0.0, 0.0), Vec3A::X, min, max), Some(4.0));
+        // starting inside
+        assert_eq!(ray_aabb_distance(Vec3A::ZERO, Vec3A::X, min, max), Some(0.0));
+        // pointing away
+        assert_eq!(ray_aabb_distance(Vec3A::new(-5.0, 0.0, 0.0), -Vec3A::X, min, max), None);
+        // parallel to a slab, outside of it
+        assert_eq!(ray_aabb_distance(Vec3A::new(-5.0, 2.0, 0.0), Vec3A::X, min, max), None);
+        // parallel to a slab, on its boundary: along a face or an edge of the box
+        assert_eq!(ray_aabb_distance(Vec3A::new(-5.0, 1.0, 0.0), Vec3A::X, min, max), Some(4.0));
+        assert_eq!(ray_aabb_distance(Vec3A::new(-5.0, -1.0, 1.0), Vec3A::X, min, max), Some(4.0));
+        // not normalized: distances are in units of the direction
+        assert_eq!(ray_aabb_distance(Vec3A::new(-5.0, 0.0, 0.0), Vec3A::X * 2.0, min, max), Some(2.0));
+        let diagonal = ray_aabb_distance(Vec3A::splat(-3.0), Vec3A::ONE.normalize(), min, max).unwrap();
+        assert!((diagonal - 2.0 * 3.0_f32.sqrt()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn raycasts() {
+        let index = index_with(&[
+            (Vec3::new(35.0, 0.0, 0.0), Vec3::ONE),
+            (Vec3::new(75.0, 0.0, 0.0), Vec3::ONE),
+            (Vec3::new(35.0, 50.0, 0.0), Vec3::ONE),
+        ]);
+        let along_x = ray(Vec3::ZERO, Vec3::X);
+        // walks several cells to the closest hit
+        assert_eq!(index.raycast(along_x, f32::MAX, |_| true), Some((entity(0), 34.0)));
+        assert_eq!(index.raycast(along_x, 30.0, |_| true), None);
+        assert_eq!(index.raycast(along_x, f32::MAX, |entity| entity != Entity::from_raw(0)), Some((entity(1), 74.0)));
+        // from outside of the grid, & backwards
+        assert_eq!(index.raycast(ray(Vec3::new(200.0, 0.0, 0.0), -Vec3::X), f32::MAX, |_| true), Some((entity(1), 124.0)));
+        // from inside of an entry
+        assert_eq!(index.raycast(ray(Vec3::new(35.0, 0.5, 0.0), Vec3::Y), f32::MAX, |_| true), Some((entity(0), 0.0)));
+        // diagonally, across cell boundaries
+        let (hit, distance) = index
+            .raycast(ray(Vec3::ZERO, Vec3::new(35.0, 50.0, 0.0)), f32::MAX, |_| true)
+            .unwrap();
+        assert_eq!(hit, entity(2));
+        assert!((distance - (Vec3::new(35.0, 50.0, 0.0).length() * (1.0 - 1.0 / 50.0))).abs() < 1e-3);
+        // along cell boundaries & the faces of the entries
+        assert_eq!(index.raycast(ray(Vec3::new(0.0, 1.0, 0.0), Vec3::X), f32::MAX, |_| true), Some((entity(0), 34.0)));
+        assert_eq!(index.raycast(ray(Vec3::new(0.0, 10.0, 0.0), Vec3::X), f32::MAX, |_| true), None);
+        // misses
+        assert_eq!(index.raycast(ray(Vec3::new(0.0, 0.0, 5.0), Vec3::X), f32::MAX, |_| true), None);
+        assert_eq!(index.raycast(ray(Vec3::ZERO, -Vec3::X), f32::MAX, |_| true), None);
+    }
+
+    #[test]
+    fn raycasts_use_oriented_bounds() {
+        let mut index = BlueprintSpatialIndex::new(10.0);
+        // a thin box rotated by 45 degrees: its world space aabb is much larger than itself
+        let world_from_local = Affine3A::from_rotation_translation(Quat::from_rotation_z(FRAC_PI_4), Vec3::ZERO);
+        index.insert(entity(0), aabb(Vec3::new(5.0, 0.1, 1.0)), world_from_local);
+        assert!(index.raycast(ray(Vec3::new(3.0, -3.0, -10.0), Vec3::Z), f32::MAX, |_| true).is_none());
+        assert_eq!(index.raycast(ray(Vec3::new(3.0, 3.0, -10.0), Vec3::Z), f32::MAX, |_| true), Some((entity(0), 9.0)));
+    }
+
+    #[test]
+    fn nearest() {
+        let index = index_with(&[
+            (Vec3::new(25.0, 0.0, 0.0), Vec3::ONE),
+            (Vec3::new(-45.0, 0.0, 0.0), Vec3::ONE),
+            (Vec3::new(0.0, 0.0, 5.0), Vec3::ONE),
+        ]);
+        assert_eq!(index.nearest(Vec3::ZERO, f32::MAX, |_| true), Some((entity(2), 4.0)));
+        // inside of an entry
+        assert_eq!(index.nearest(Vec3::new(0.0, 0.0, 5.5), f32::MAX, |_| true), Some((entity(2), 0.0)));
+        // in further rings
+        let not_2 = |entity: Entity| entity != Entity::from_raw(2);
+        assert_eq!(index.nearest(Vec3::ZERO, f32::MAX, not_2), Some((entity(0), 24.0)));
+        assert_eq!(index.nearest(Vec3::new(-20.0, 0.0, 0.0), f32::MAX, not_2), Some((entity(1), 24.0)));
+        // a closer entry in a further cell: on a cell boundary, 2 cells away from the point
+        assert_eq!(index.nearest(Vec3::new(9.9, 0.0, 0.0), f32::MAX, not_2), Some((entity(0), 14.1)));
+        assert_eq!(index.nearest(Vec3::ZERO, 3.0, |_| true), None);
+        // from outside of the grid
+        assert_eq!(index.nearest(Vec3::new(500.0, 0.0, 0.0), f32::MAX, |_| true), Some((entity(0), 474.0)));
+    }
+
+    #[test]
+    fn frustum_queries() {
+        let index = index_with(&[
+            // in front of the camera
+            (Vec3::new(0.0, 0.0, -10.0), Vec3::ONE),
+            // behind it
+            (Vec3::new(0.0, 0.0, 10.0), Vec3::ONE),
+            // on the side
+            (Vec3::new(30.0, 0.0, -10.0), Vec3::ONE),
+            // beyond the far plane
+            (Vec3::new(0.0, 0.0, -150.0), Vec3::ONE),
+            // crossing the side plane, with its center outside of the frustum
+            (Vec3::new(-11.5, 0.0, -10.0), Vec3::splat(2.0)),
+        ]);
+        assert_eq!(sorted(index.entities_in_frustum(&camera_frustum())), vec![entity(0), entity(4)]);
+    }
+}
@@ -1,13 +1,17 @@
-use std::path::Path;
+use std::{path::Path, sync::Arc, time::Duration};
 //use bevy::scene::SceneRoot as BevySceneBundle;
-use bevy::{gltf::Gltf, prelude::*, scene::SceneInstance, utils::hashbrown::HashMap};
+use bevy::{
+    animation::AnimationTarget, gltf::Gltf, prelude::*, scene::SceneInstance, utils::hashbrown::HashMap,
+};
  
 use crate::{
-    AnimationInfos, AssetLoadTracker, AssetToBlueprintInstancesMapper, BlueprintAnimationInfosLink,
-    BlueprintAnimationPlayerLink, BlueprintAnimations, BlueprintAssetsLoadState,
-    BlueprintAssetsLoaded, BlueprintAssetsNotLoaded, BlueprintMetaLoaded, BlueprintMetaLoading,
-    BlueprintPreloadAssets, InstanceAnimationInfosLink, InstanceAnimationPlayerLink,
-    InstanceAnimations, WatchingForChanges,
+    add_markers_to_clips, blueprint_meta_path, bone_group_targets, build_animation_graph, AnimationGraphInfos,
+    AnimationInfos, AnimationMarkerClips, AnimationMarkers,
+    AssetLoadTracker, AssetToBlueprintInstancesMapper, BlenvyComponent,
+    BlueprintAnimationGraphCache, BlueprintAnimationInfosLink, BlueprintAnimationPlayerLink,
+    BlueprintAnimations, BlueprintAssetsLoadState, BlueprintAssetsLoaded, BlueprintAssetsNotLoaded,
+    BlueprintMetaLoaded, BlueprintMetaLoading, BlueprintPreloadAssets, InstanceAnimationInfosLink,
+    InstanceAnimationPlayerLink, InstanceAnimations, WatchingForChanges,
 };
 
 /// this is a flag component for our levels/game world
@@ -17,7 +21,7 @@ pub struct GameWorldTag;
 /// Main component for the blueprints
 /// has both name & path of the blueprint to enable injecting the data from the correct blueprint
 /// into the entity that contains this component
-#[derive(Component, Reflect, Default, Debug)]
+#[derive(Component, Reflect, Default, Debug, BlenvyComponent)]
 #[reflect(Component)]
 pub struct BlueprintInfo {
     pub name: String,
@@ -36,7 +40,7 @@ impl BlueprintInfo {
 }
 
 /// flag component needed to signify the intent to spawn a Blueprint
-#[derive(Component, Reflect, Default, Debug)]
+#[derive(Component, Reflect, Default, Debug, BlenvyComponent)]
 #[reflect(Component)]
 pub struct SpawnBlueprint;
 
@@ -54,7 +58,7 @@ pub struct AddToGameWorld;
 /// helper component, just to transfer child data
 pub(crate) struct OriginalChildren(pub Vec<Entity>);
 
-#[derive(Component, Reflect, Default, Debug)]
+#[derive(Component, Reflect, Default, Debug, BlenvyComponent)]
 #[reflect(Component)]
 /// You can add this component to a blueprint instance, and the instance will be hidden until it is ready
 /// You usually want to use this for worlds/level spawning , or dynamic spawning at runtime, but not when you are adding blueprint instances to an existing entity
@@ -149,10 +153,7 @@ pub(super) fn blueprints_prepare_metadata_file_for_spawn(
             "Step 1: spawn request detected: loading metadata file for {:?}",
             blueprint_info
         );
-        let blueprint_path = blueprint_info.path.clone();
-        let metadata_path = blueprint_path
-            .replace(".glb", ".meta.ron")
-            .replace(".gltf", ".meta.ron"); // FIXME: horrible
+        let metadata_path = blueprint_meta_path(&blueprint_info.path);
         let mut asset_infos: Vec<AssetLoadTracker> = vec![];
         //let foo_handle:Handle<BlueprintPreloadAssets> = asset_server.load(metadata_path);
         let untyped_handle = asset_server.load_untyped(metadata_path.clone());
@@ -456,6 +457,7 @@ pub(crate) fn blueprints_assets_loaded(
     assets_gltf: Res<Assets<Gltf>>,
     asset_server: Res<AssetServer>,
     mut graphs: ResMut<Assets<AnimationGraph>>,
+    mut graph_cache: ResMut<BlueprintAnimationGraphCache>,
     mut commands: Commands,
 ) {
     for (entity, blueprint_info, maybe_transform, name) in spawn_placeholders.iter() {
@@ -487,27 +489,36 @@ pub(crate) fn blueprints_assets_loaded(
         }
         info!("Step 3:2");
 
-        // Build animation graph
-        let mut graph = AnimationGraph::new();
-        let mut named_animations: HashMap<String, Handle<AnimationClip>> = HashMap::new();
-        let mut named_indices: HashMap<String, AnimationNodeIndex> = HashMap::new();
-
-        for (key, clip) in blueprint_gltf.named_animations.iter() {
-            named_animations.insert(key.to_string(), clip.clone());
-            let index = graph.add_clip(clip.clone(), 1.0, graph.root);
-            named_indices.insert(key.to_string(), index);
-        }
-        let graph_handle = graphs.add(graph);
+        // Build animation graph, shared by all the instances of the blueprint
+        let animations = match graph_cache.get(&blueprint_info.path) {
+            Some(animations) => animations.clone(),
+            None => {
+                let mut graph = AnimationGraph::new();
+                let mut named_animations: HashMap<String, Handle<AnimationClip>> = HashMap::new();
+                let mut named_indices: HashMap<String, AnimationNodeIndex> = HashMap::new();
+
+                for (key, clip) in blueprint_gltf.named_animations.iter() {
+                    named_animations.insert(key.to_string(), clip.clone());
+                    let index = graph.add_clip(clip.clone(), 1.0, graph.root);
+                    named_indices.insert(key.to_string(), index);
+                }
+                let animations = BlueprintAnimations {
+                    named_animations: Arc::new(named_animations),
+                    named_indices: Arc::new(named_indices),
+                    graph: graphs.add(graph),
+                };
+                graph_cache
+                    .animations
+                    .insert(blueprint_info.path.clone(), animations.clone());
+                animations
+            }
+        };
         info!("Step 3:3");
         // Insert components into the entity
         commands.entity(entity).insert((
             SceneRoot(scene),
             OriginalChildren(original_children),
-            BlueprintAnimations {
-                named_animations,
-                named_indices,
-                graph: graph_handle,
-            },
+            animations,
         ));
         info!("Step 3:1");
     }
@@ -642,6 +653,8 @@ pub struct BlueprintReadyForPostProcess;
 /// - it copies the blueprint's root components to the entity it was spawned on (original entity)
 /// - it copies the children of the blueprint scene into the original entity
 /// - it adds an `AnimationLink` component containing the entity that has the `AnimationPlayer` so that animations can be controlled from the original entity
+/// - it builds the animation graph described by `AnimationGraphInfos`, if any
+/// - it adds the `AnimationMarkers` as events of the animation clips
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn blueprints_cleanup_spawned_scene(
     blueprint_scenes: Query<
@@ -650,19 +663,26 @@ pub(crate) fn blueprints_cleanup_spawned_scene(
             &Children,
             &OriginalChildren,
             Option<&Name>,
+            &BlueprintInfo,
             &BlueprintAnimations,
         ),
         Added<BlueprintChildrenReady>,
     >,
-    animation_players: Query<(Entity, &Parent), With<AnimationPlayer>>,
+    mut animation_players: Query<(Entity, &Parent, &mut AnimationPlayer)>,
     all_children: Query<&Children>,
     all_parents: Query<&Parent>,
-    with_animation_infos: Query<&AnimationInfos>,
-    anims: Query<&BlueprintAnimations>,
+    with_animation_infos: Query<(&AnimationInfos, Option<&AnimationMarkers>)>,
+    all_graph_infos: Query<&AnimationGraphInfos>,
+    animation_targets: Query<(&Name, &AnimationTarget)>,
+    mut graphs: ResMut<Assets<AnimationGraph>>,
+    mut graph_cache: ResMut<BlueprintAnimationGraphCache>,
+    mut animation_clips: ResMut<Assets<AnimationClip>>,
+    mut marker_clips: ResMut<AnimationMarkerClips>,
+    type_registry: Res<AppTypeRegistry>,
     mut commands: bevy::ecs::system::Commands,
     all_names: Query<&Name>,
 ) {
-    for (original, children, original_children, name, animations) in blueprint_scenes.iter() {
+    for (original, children, original_children, name, blueprint_info, animations) in blueprint_scenes.iter() {
         info!("Step 5: Cleaning up spawned scene {:?}", name);
 
         if children.len() == 0 {
@@ -701,29 +721,88 @@ pub(crate) fn blueprints_cleanup_spawned_scene(
 
         // Handle animations if present
         if !animations.named_animations.is_empty() {
-    for (entity_with_player, parent) in animation_players.iter() {
-        if parent.get() == blueprint_root_entity {
-            debug!(
-                "FOUND ANIMATION PLAYER FOR {:?} {:?} ",
-                all_names.get(original),
-                all_names.get(entity_with_player)
-            );
-            commands
-                .entity(original)
-                .insert(BlueprintAnimationPlayerLink(entity_with_player));
+            // cheap to clone: the names & indices are shared by all the instances of the blueprint
+            let mut animations = animations.clone();
+            let blueprint_players: Vec<Entity> = animation_players
+                .iter()
+                .filter(|(_, parent, _)| parent.get() == blueprint_root_entity)
+                .map(|(entity_with_player, _, _)| entity_with_player)
+                .collect();
+            for entity_with_player in blueprint_players {
+                debug!(
+                    "FOUND ANIMATION PLAYER FOR {:?} {:?} ",
+                    all_names.get(original),
+                    all_names.get(entity_with_player)
+                );
+                commands
+                    .entity(original)
+                    .insert(BlueprintAnimationPlayerLink(entity_with_player));
+                let mut transitions = AnimationTransitions::new();
+
+                // the graph described on the Blender side (if any) replaces the flat one
+                let graph_infos = all_graph_infos
+                    .get(entity_with_player)
+                    .or_else(|_| all_graph_infos.get(blueprint_root_entity));
+                if let Ok(graph_infos) = graph_infos {
+                    // only built once per blueprint, the other instances get it from the cache
+                    match graph_cache.get(&blueprint_info.path) {
+                        Some(cached) if graph_cache.from_graph_infos.contains(&blueprint_info.path) => {
+                            animations = cached.clone();
+                        }
+                        _ => {
+                            let group_targets = bone_group_targets(
+                                graph_infos,
+                                entity_with_player,
+                                &all_children,
+                                &animation_targets,
+                            );
+                            let (graph, named_indices) =
+                                build_animation_graph(graph_infos, &animations.named_animations, &group_targets);
+                            // a new graph asset, the flat one stays untouched for the other users of the blueprint's animations
+                            animations.graph = graphs.add(graph);
+                            animations.named_indices = Arc::new(named_indices);
+                            graph_cache
+                                .animations
+                                .insert(blueprint_info.path.clone(), animations.clone());
+                            graph_cache.from_graph_infos.insert(blueprint_info.path.clone());
+                        }
+                    }
+                    commands.entity(original).insert(animations.clone());
+
+                    if let Ok((_, _, mut player)) = animation_players.get_mut(entity_with_player) {
+                        for animation_name in graph_infos.default_animations.iter() {
+                            let Some(index) = animations.named_indices.get(animation_name) else {
+                                warn!("Unknown default animation {}", animation_name);
+                                continue;
+                            };
+                            // the first one is the main animation, that gets faded out by later crossfades
+                            if transitions.get_main_animation().is_none() {
+                                transitions.play(&mut player, *index, Duration::ZERO).repeat();
+                            } else {
+                                player.play(*index).repeat();
+                            }
+                        }
+                    }
+                }
 
-            let transitions = AnimationTransitions::new();
-            commands
-                .entity(entity_with_player)
-                .insert(transitions)
-                .insert(AnimationGraphHandle(animations.graph.clone()));
-                //.insert(animations.graph.clone());
+                commands
+                    .entity(entity_with_player)
+                    .insert(transitions)
+                    .insert(AnimationGraphHandle(animations.graph.clone()));
             }
-         }
-       
 
             for child in all_children.iter_descendants(blueprint_root_entity) {
-                if with_animation_infos.get(child).is_ok() {
+                if let Ok((animation_infos, animation_markers)) = with_animation_infos.get(child) {
+                    if let Some(animation_markers) = animation_markers {
+                        add_markers_to_clips(
+                            &animations.named_animations,
+                            animation_infos,
+                            animation_markers,
+                            &mut animation_clips,
+                            &type_registry.read(),
+                            &mut marker_clips,
+                        );
+                    }
                     if animation_players.get(child).is_ok() {
                         debug!(
                             "found BLUEPRINT animation player for {:?} at {:?} Root: {:?}",
@@ -737,13 +816,12 @@ pub(crate) fn blueprints_cleanup_spawned_scene(
                     } else {
                         for parent in all_parents.iter_ancestors(child) {
                             if animation_players.get(parent).is_ok() {
-                                let original_animations = anims.get(original).unwrap();
                                 commands.entity(child).insert((
                                     InstanceAnimationPlayerLink(parent),
                                     InstanceAnimations {
-                                        named_animations: original_animations.named_animations.clone(),
-                                        named_indices: original_animations.named_indices.clone(),
-                                        graph: original_animations.graph.clone(),
+                                        named_animations: animations.named_animations.clone(),
+                                        named_indices: animations.named_indices.clone(),
+                                        graph: animations.graph.clone(),
                                     },
                                 ));
                             }
@@ -4,6 +4,9 @@ pub use utils::*;
 pub mod ronstring_to_reflect_component;
 pub use ronstring_to_reflect_component::*;
 
+pub mod reflect_component_to_ronstring;
+pub use reflect_component_to_ronstring::*;
+
 pub mod process_gltfs;
 pub use process_gltfs::*;
 
@@ -15,6 +18,8 @@ use bevy::{
     reflect::Reflect,
 };
 
+use crate::{validate_extras_enabled, validate_gltf_extras, ExtrasValidationReport};
+
 /// A Bevy plugin for extracting components from gltf files and automatically adding them to the relevant entities
 /// It will automatically run every time you load a gltf file
 /// Add this plugin to your Bevy app to get access to this feature
@@ -66,9 +71,15 @@ impl Plugin for ComponentsFromGltfPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(blender_settings::plugin)
             .register_type::<GltfProcessed>()
+            .init_resource::<ExtrasValidationReport>()
             .add_systems(
                 Update,
-                (add_components_from_gltf_extras).in_set(GltfComponentsSet::Injection),
+                (
+                    validate_gltf_extras.run_if(validate_extras_enabled),
+                    add_components_from_gltf_extras,
+                )
+                    .chain()
+                    .in_set(GltfComponentsSet::Injection),
             );
     }
 }
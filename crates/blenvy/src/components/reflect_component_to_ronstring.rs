@@ -0,0 +1,291 @@
+use std::collections::BTreeMap;
+
+use bevy::ecs::{entity::Entity, reflect::ReflectComponent, world::World};
+use bevy::log::warn;
+use bevy::reflect::serde::TypedReflectSerializer;
+use bevy::reflect::{PartialReflect, ReflectRef, ReflectSerialize, TypeRegistry, VariantType};
+use bevy::scene::SceneFilter;
+
+/// Serializes a reflected value into the RON shape written by the Blender add-on for the values of `bevy_components`,
+/// ie the inverse of [`crate::extras_to_component_ron_strings`]:
+/// structs as `(field: value)`, tuples, tuple structs & arrays as `(a, b)`, enums as `Variant`, `Variant(a)` or `Variant(field: value)`,
+/// lists as `[a, b]`, maps as `{key: value}`.
+/// Types with their own serialization (`#[reflect(Serialize)]`: numbers, strings, `Vec3`, `Quat` etc) use it, so that the output
+/// can always be loaded back by [`crate::ronstring_to_reflect_component`]
+pub fn reflect_to_ron_string(
+    value: &dyn PartialReflect,
+    type_registry: &TypeRegistry,
+) -> Result<String, String> {
+    let mut output = String::new();
+    write_value(value, type_registry, &mut output)?;
+    Ok(output)
+}
+
+/// Serializes the components of an entity (those registered with `#[reflect(Component)]` & allowed by the filter)
+/// into a map of type paths to RON strings, ie the content of `bevy_components`, see [`bevy_components_string`]
+/// Components that cannot be serialized are skipped with a warning
+pub fn entity_to_component_ron_strings(
+    world: &World,
+    entity: Entity,
+    filter: &SceneFilter,
+    type_registry: &TypeRegistry,
+) -> BTreeMap<String, String> {
+    let mut components = BTreeMap::new();
+    let Ok(entity_ref) = world.get_entity(entity) else {
+        warn!("Entity {:?} does not exist", entity);
+        return components;
+    };
+    for component_id in entity_ref.archetype().components() {
+        let Some(type_id) = world
+            .components()
+            .get_info(component_id)
+            .and_then(|info| info.type_id())
+        else {
+            continue;
+        };
+        if !filter.is_allowed_by_id(type_id) {
+            continue;
+        }
+        let Some(registration) = type_registry.get(type_id) else {
+            continue;
+        };
+        let Some(component) = registration
+            .data::<ReflectComponent>()
+            .and_then(|reflect_component| reflect_component.reflect(entity_ref))
+        else {
+            continue;
+        };
+        let type_path = registration.type_info().type_path();
+        match reflect_to_ron_string(component.as_partial_reflect(), type_registry) {
+            Ok(ron_string) => {
+                components.insert(type_path.to_string(), ron_string);
+            }
+            Err(e) => warn!("Failed to serialize component '{}': {}", type_path, e),
+        }
+    }
+    components
+}
+
+/// The value of the `bevy_components` custom property / gltf extra, as written by the Blender add-on:
+/// a json object of type paths to RON strings
+pub fn bevy_components_string(components: &BTreeMap<String, String>) -> String {
+    serde_json::to_string(components).expect("a map of strings should serialize correctly")
+}
+
+fn write_value(
+    value: &dyn PartialReflect,
+    type_registry: &TypeRegistry,
+    output: &mut String,
+) -> Result<(), String> {
+    let serializable = value
+        .get_represented_type_info()
+        .and_then(|info| type_registry.get(info.type_id()))
+        .is_some_and(|registration| registration.data::<ReflectSerialize>().is_some());
+    if serializable {
+        return write_serialized(value, type_registry, output);
+    }
+    match value.reflect_ref() {
+        ReflectRef::Struct(value) => {
+            let fields = (0..value.field_len()).filter_map(|index| {
+                Some((value.name_at(index)?.to_string(), value.field_at(index)?))
+            });
+            write_named_fields(fields, type_registry, output)
+        }
+        ReflectRef::TupleStruct(value) => write_sequence("(", ")", value.iter_fields(), type_registry, output),
+        ReflectRef::Tuple(value) => write_sequence("(", ")", value.iter_fields(), type_registry, output),
+        ReflectRef::List(value) => write_sequence("[", "]", value.iter(), type_registry, output),
+        ReflectRef::Array(value) => write_sequence("(", ")", value.iter(), type_registry, output),
+        ReflectRef::Set(value) => write_sequence("[", "]", value.iter(), type_registry, output),
+        ReflectRef::Map(value) => {
+            output.push('{');
+            for (index, (key, value)) in value.iter().enumerate() {
+                if index > 0 {
+                    output.push_str(", ");
+                }
+                write_value(key, type_registry, output)?;
+                output.push_str(": ");
+                write_value(value, type_registry, output)?;
+            }
+            output.push('}');
+            Ok(())
+        }
+        ReflectRef::Enum(value) => {
+            output.push_str(value.variant_name());
+            match value.variant_type() {
+                VariantType::Unit => Ok(()),
+                VariantType::Tuple => write_sequence(
+                    "(",
+                    ")",
+                    value.iter_fields().map(|field| field.value()),
+                    type_registry,
+                    output,
+                ),
+                VariantType::Struct => {
+                    let fields = value
+                        .iter_fields()
+                        .filter_map(|field| Some((field.name()?.to_string(), field.value())));
+                    write_named_fields(fields, type_registry, output)
+                }
+            }
+        }
+        ReflectRef::Opaque(value) => write_serialized(value, type_registry, output),
+        #[allow(unreachable_patterns)]
+        _ => Err(format!(
+            "unsupported value '{}'",
+            value.reflect_type_path()
+        )),
+    }
+}
+
+fn write_serialized(
+    value: &dyn PartialReflect,
+    type_registry: &TypeRegistry,
+    output: &mut String,
+) -> Result<(), String> {
+    let serializer = TypedReflectSerializer::new(value, type_registry);
+    let ron_string = ron::to_string(&serializer)
+        .map_err(|e| format!("failed to serialize '{}': {}", value.reflect_type_path(), e))?;
+    output.push_str(&ron_string);
+    Ok(())
+}
+
+fn write_named_fields<'a>(
+    fields: impl Iterator<Item = (String, &'a dyn PartialReflect)>,
+    type_registry: &TypeRegistry,
+    output: &mut String,
+) -> Result<(), String> {
+    output.push('(');
+    for (index, (name, value)) in fields.enumerate() {
+        if index > 0 {
+            output.push_str(", ");
+        }
+        output.push_str(&name);
+        output.push_str(": ");
+        write_value(value, type_registry, output)?;
+    }
+    output.push(')');
+    Ok(())
+}
+
+fn write_sequence<'a>(
+    open: &str,
+    close: &str,
+    values: impl Iterator<Item = &'a dyn PartialReflect>,
+    type_registry: &TypeRegistry,
+    output: &mut String,
+) -> Result<(), String> {
+    output.push_str(open);
+    for (index, value) in values.enumerate() {
+        if index > 0 {
+            output.push_str(", ");
+        }
+        write_value(value, type_registry, output)?;
+    }
+    output.push_str(close);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::color::{Color, Srgba};
+    use bevy::math::Vec3;
+    use bevy::reflect::{FromReflect, GetTypeRegistration, Reflect, TypePath};
+
+    use super::*;
+    use crate::ronstring_to_reflect_component;
+
+    #[derive(Reflect, Debug, Clone, PartialEq)]
+    struct TestStruct {
+        name: String,
+        speed: f32,
+        position: Vec3,
+        color: Color,
+        target: Option<Vec3>,
+        missing: Option<String>,
+        nested: TestTupleStruct,
+        tags: Vec<String>,
+    }
+
+    #[derive(Reflect, Debug, Clone, PartialEq)]
+    struct TestTupleStruct(u32, bool);
+
+    #[derive(Reflect, Debug, Clone, PartialEq)]
+    enum TestEnum {
+        Unit,
+        Tuple(f32, String),
+        Struct { value: i32, option: Option<TestTupleStruct> },
+    }
+
+    /// serializes the value into `bevy_components` gltf extras, then loads it back
+    fn round_trip<T: Reflect + FromReflect + TypePath + GetTypeRegistration>(value: &T) -> T {
+        let mut type_registry = TypeRegistry::default();
+        type_registry.register::<T>();
+
+        let ron_string = reflect_to_ron_string(value.as_partial_reflect(), &type_registry).unwrap();
+        let components = BTreeMap::from([(T::type_path().to_string(), ron_string)]);
+        let extras = ron::to_string(&BTreeMap::from([("bevy_components", bevy_components_string(&components))])).unwrap();
+
+        let mut loaded = ronstring_to_reflect_component(&extras, &type_registry);
+        assert_eq!(loaded.len(), 1, "failed to load back {extras}");
+        let (component, registration) = loaded.remove(0);
+        assert_eq!(registration.type_info().type_path(), T::type_path());
+        *component.downcast::<T>().unwrap()
+    }
+
+    #[test]
+    fn round_trip_struct() {
+        let value = TestStruct {
+            name: "Bob \"the\" builder".into(),
+            speed: -1.5,
+            position: Vec3::new(1.0, 2.5, -3.0),
+            color: Color::Srgba(Srgba::new(0.1, 0.2, 0.3, 0.5)),
+            target: Some(Vec3::Y),
+            missing: None,
+            nested: TestTupleStruct(7, true),
+            tags: vec!["a".into(), "b".into()],
+        };
+        assert_eq!(round_trip(&value), value);
+    }
+
+    #[test]
+    fn round_trip_tuple_struct() {
+        let value = TestTupleStruct(42, false);
+        assert_eq!(round_trip(&value), value);
+    }
+
+    #[test]
+    fn round_trip_enum() {
+        for value in [
+            TestEnum::Unit,
+            TestEnum::Tuple(0.25, "tuple".into()),
+            TestEnum::Struct {
+                value: -3,
+                option: Some(TestTupleStruct(1, true)),
+            },
+            TestEnum::Struct { value: 3, option: None },
+        ] {
+            assert_eq!(round_trip(&value), value);
+        }
+    }
+
+    #[test]
+    fn round_trip_math_types() {
+        assert_eq!(round_trip(&Vec3::new(0.5, -1.0, 1e6)), Vec3::new(0.5, -1.0, 1e6));
+        let color = Color::linear_rgba(1.0, 0.5, 0.0, 1.0);
+        assert_eq!(round_trip(&color), color);
+    }
+
+    #[test]
+    fn written_shape() {
+        let mut type_registry = TypeRegistry::default();
+        type_registry.register::<TestEnum>();
+        let value = TestEnum::Struct {
+            value: 1,
+            option: Some(TestTupleStruct(2, true)),
+        };
+        assert_eq!(
+            reflect_to_ron_string(value.as_partial_reflect(), &type_registry).unwrap(),
+            "Struct(value: 1, option: Some((2, true)))"
+        );
+    }
+}
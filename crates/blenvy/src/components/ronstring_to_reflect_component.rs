@@ -1,144 +1,138 @@
 use bevy::log::{debug, warn};
 use bevy::reflect::serde::ReflectDeserializer;
-use bevy::reflect::{Reflect, TypeRegistration, TypeRegistry, PartialReflect};
+use bevy::reflect::{Reflect, ReflectFromReflect, TypeRegistration, TypeRegistry};
 use bevy::utils::HashMap;
 use ron::Value;
 use serde::de::DeserializeSeed;
 
 use super::capitalize_first_letter;
 
-pub fn ronstring_to_reflect_component(
-    ron_string: &str,
-    type_registry: &TypeRegistry,
-) -> Vec<(Box<dyn Reflect>, TypeRegistration)> {
-    let lookup: HashMap<String, Value> = match ron::from_str(ron_string) {
-        Ok(map) => map,
-        Err(e) => {
-            warn!("Failed to parse RON string '{}': {:?}", ron_string, e);
-            return Vec::new();
+/// Name of a component as found inside gltf extras
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtrasComponentName {
+    /// full type path, as used in the `bevy_components` entry written by the Blender add-on
+    TypePath(String),
+    /// legacy format (`component: foo` / `foo`): capitalized short type name
+    ShortName(String),
+}
+
+impl ExtrasComponentName {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ExtrasComponentName::TypePath(name) | ExtrasComponentName::ShortName(name) => name,
         }
-    };
+    }
+
+    /// finds the matching type registration, if any
+    pub fn registration<'a>(&self, type_registry: &'a TypeRegistry) -> Option<&'a TypeRegistration> {
+        match self {
+            ExtrasComponentName::TypePath(path) => type_registry.get_with_type_path(path),
+            ExtrasComponentName::ShortName(name) => type_registry.get_with_short_type_path(name),
+        }
+    }
+}
+
+/// Splits the RON string of gltf extras into a list of component names & their (RON) values
+/// This does not need a type registry, so it can be used for tooling, validation etc
+pub fn extras_to_component_ron_strings(
+    ron_string: &str,
+) -> Result<Vec<(ExtrasComponentName, String)>, String> {
+    let lookup: HashMap<String, Value> = ron::from_str(ron_string)
+        .map_err(|e| format!("Failed to parse RON string '{}': {:?}", ron_string, e))?;
 
     let mut components = Vec::new();
     for (name, value) in lookup {
-        let parsed_value = match value.clone() {
-            Value::String(str) => str,
-            _ => match ron::to_string(&value) {
-                Ok(s) => s,
-                Err(e) => {
-                    warn!("Failed to serialize value for '{}': {:?}", name, e);
-                    continue;
-                }
-            },
+        let Some(parsed_value) = value_to_ron_string(&name, value) else {
+            continue;
         };
 
         if name == "bevy_components" {
-            bevy_components_string_to_components(&parsed_value, type_registry, &mut components);
+            let lookup: HashMap<String, Value> = ron::from_str(&parsed_value)
+                .map_err(|e| format!("Failed to parse bevy_components RON: {:?}", e))?;
+            for (key, value) in lookup {
+                if let Some(parsed_value) = value_to_ron_string(&key, value) {
+                    components.push((ExtrasComponentName::TypePath(key), parsed_value));
+                }
+            }
         } else {
-            components_string_to_components(&name, value, &parsed_value, type_registry, &mut components);
+            let type_string = name.replace("component: ", "").trim().to_string();
+            components.push((
+                ExtrasComponentName::ShortName(capitalize_first_letter(&type_string)),
+                parsed_value,
+            ));
         }
     }
-    components
+    Ok(components)
 }
 
-fn components_string_to_components(
-    name: &str,
-    value: Value,
-    parsed_value: &str,
+fn value_to_ron_string(name: &str, value: Value) -> Option<String> {
+    match value {
+        Value::String(str) => Some(str),
+        _ => match ron::to_string(&value) {
+            Ok(s) => Some(s),
+            Err(e) => {
+                warn!("Failed to serialize value for '{}': {:?}", name, e);
+                None
+            }
+        },
+    }
+}
+
+pub fn ronstring_to_reflect_component(
+    ron_string: &str,
     type_registry: &TypeRegistry,
-    components: &mut Vec<(Box<dyn Reflect>, TypeRegistration)>,
-) {
-    let type_string = name.replace("component: ", "").trim().to_string();
-    let capitalized_type_name = capitalize_first_letter(&type_string);
+) -> Vec<(Box<dyn Reflect>, TypeRegistration)> {
+    let component_strings = match extras_to_component_ron_strings(ron_string) {
+        Ok(component_strings) => component_strings,
+        Err(e) => {
+            warn!("{}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut components = Vec::new();
+    for (name, parsed_value) in component_strings {
+        let Some(type_registration) = name.registration(type_registry) else {
+            warn!("No type registration for '{}'", name.as_str());
+            continue;
+        };
 
-    if let Some(type_registration) = type_registry.get_with_short_type_path(&capitalized_type_name) {
         let ron_string = format!(
             "{{ \"{}\": {} }}",
             type_registration.type_info().type_path(),
             parsed_value
         );
-        warn!("Component data RON string: {}", ron_string);
+        debug!("Component data RON string: {}", ron_string);
 
         let mut deserializer = match ron::Deserializer::from_str(&ron_string) {
             Ok(deserializer) => deserializer,
             Err(e) => {
-                warn!("Failed to create deserializer for '{}': {:?}", name, e);
-                return;
+                warn!("Failed to create deserializer for '{}': {:?}", name.as_str(), e);
+                continue;
             }
         };
 
         let reflect_deserializer = ReflectDeserializer::new(type_registry);
         match reflect_deserializer.deserialize(&mut deserializer) {
             Ok(component) => {
+                // the deserializer returns dynamic types (ie `DynamicStruct`) for anything that is not deserialized by serde directly,
+                // convert them to the concrete type if possible
+                let component = match type_registration.data::<ReflectFromReflect>() {
+                    Some(from_reflect) => from_reflect
+                        .from_reflect(component.as_partial_reflect())
+                        .map(|component| component.into_partial_reflect())
+                        .unwrap_or(component),
+                    None => component,
+                };
                 if let Ok(component_reflect) = component.try_into_reflect() {
                     components.push((component_reflect, type_registration.clone()));
-                    debug!("Successfully registered component '{}'", capitalized_type_name);
+                    debug!("Successfully registered component '{}'", name.as_str());
                 } else {
-                    warn!("Component '{}' lacks FromReflect or type mismatch", capitalized_type_name);
+                    warn!("Component '{}' lacks FromReflect or type mismatch", name.as_str());
                 }
             }
-            Err(e) => warn!("Failed to deserialize component '{}': {:?}", name, e),
+            Err(e) => warn!("Failed to deserialize component '{}': {:?}", name.as_str(), e),
         }
-    } else {
-        warn!("No type registration for '{}'", capitalized_type_name);
     }
+    components
 }
-
-fn bevy_components_string_to_components(
-    parsed_value: &str,
-    type_registry: &TypeRegistry,
-    components: &mut Vec<(Box<dyn Reflect>, TypeRegistration)>,
-) {
-    let lookup: HashMap<String, Value> = match ron::from_str(parsed_value) {
-        Ok(map) => map,
-        Err(e) => {
-            warn!("Failed to parse bevy_components RON: {:?}", e);
-            return;
-        }
-    };
-
-    for (key, value) in lookup {
-        let parsed_value = match value {
-            Value::String(str) => str,
-            _ => match ron::to_string(&value) {
-                Ok(s) => s,
-                Err(e) => {
-                    warn!("Failed to serialize value for '{}': {:?}", key, e);
-                    continue;
-                }
-            },
-        };
-
-        if let Some(type_registration) = type_registry.get_with_type_path(&key) {
-            let ron_string = format!(
-                "{{ \"{}\": {} }}",
-                type_registration.type_info().type_path(),
-                parsed_value
-            );
-            warn!("Component data RON string: {}", ron_string);
-
-            let mut deserializer = match ron::Deserializer::from_str(&ron_string) {
-                Ok(deserializer) => deserializer,
-                Err(e) => {
-                    warn!("Failed to create deserializer for '{}': {:?}", key, e);
-                    continue;
-                }
-            };
-
-            let reflect_deserializer = ReflectDeserializer::new(type_registry);
-            match reflect_deserializer.deserialize(&mut deserializer) {
-                Ok(component) => {
-                    if let Ok(component_reflect) = component.try_into_reflect() {
-                        components.push((component_reflect, type_registration.clone()));
-                        debug!("Successfully registered component '{}'", key);
-                    } else {
-                        warn!("Component '{}' lacks FromReflect or type mismatch", key);
-                    }
-                }
-                Err(e) => warn!("Failed to deserialize component '{}': {:?}", key, e),
-            }
-        } else {
-            warn!("No type registration for '{}'", key);
-        }
-    }
-}
\ No newline at end of file
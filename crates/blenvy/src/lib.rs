@@ -1,8 +1,12 @@
 #![doc = include_str!("../../../README.md")]
 
-use bevy::{render::primitives::Aabb, utils::HashMap};
+// lets `#[derive(BlenvyComponent)]` (which refers to `::blenvy`) be used inside this crate
+extern crate self as blenvy;
+
 use std::path::PathBuf;
 
+pub use blenvy_derive::BlenvyComponent;
+
 pub mod components;
 pub use components::*;
 
@@ -15,6 +19,9 @@ pub use blueprints::*;
 pub mod save_load;
 pub use save_load::*;
 
+pub mod lint;
+pub use lint::*;
+
 #[derive(Clone, Resource)]
 pub struct BlenvyConfig {
     // registry
@@ -23,12 +30,8 @@ pub struct BlenvyConfig {
     pub(crate) registry_component_filter: SceneFilter,
     #[allow(dead_code)]
     pub(crate) registry_resource_filter: SceneFilter,
-
-    // blueprints
-    pub(crate) aabb_cache: HashMap<String, Aabb>, // cache for aabbs
-
-    // taz being stupid
-    pub(crate) materials_cache: HashMap<String, Handle<StandardMaterial>>, // cache for materials
+    pub(crate) registry_type_rules: RegistryTypeRules,
+    pub(crate) validate_extras: bool,
 
     // save & load
     pub(crate) save_component_filter: SceneFilter,
@@ -45,6 +48,10 @@ pub struct BlenvyPlugin {
 
     pub registry_component_filter: SceneFilter,
     pub registry_resource_filter: SceneFilter,
+    /// include/exclude types from the registry export by type path, component/resource & marker attribute, see `RegistryTypeRules`
+    pub registry_type_rules: RegistryTypeRules,
+    /// check all components found in gltf extras against the registry, see `ExtrasValidationReport`
+    pub validate_extras: bool,
 
     // for save & load
     pub save_component_filter: SceneFilter,
@@ -59,6 +66,8 @@ impl Default for BlenvyPlugin {
             registry_save_path: PathBuf::from("registry.json"), // relative to assets folder
             registry_component_filter: SceneFilter::default(),
             registry_resource_filter: SceneFilter::default(),
+            registry_type_rules: RegistryTypeRules::default(),
+            validate_extras: false,
 
             save_component_filter: SceneFilter::default(),
             save_resource_filter: SceneFilter::default(),
@@ -82,10 +91,8 @@ impl Plugin for BlenvyPlugin {
             registry_save_path: self.registry_save_path.clone(),
             registry_component_filter: self.registry_component_filter.clone(),
             registry_resource_filter: self.registry_resource_filter.clone(),
-
-            aabb_cache: HashMap::new(),
-
-            materials_cache: HashMap::new(),
+            registry_type_rules: self.registry_type_rules.clone(),
+            validate_extras: self.validate_extras,
 
             save_component_filter: self.save_component_filter.clone(),
             save_resource_filter: self.save_resource_filter.clone(),
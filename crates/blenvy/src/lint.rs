@@ -0,0 +1,542 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    blueprint_meta_path, extras_to_component_ron_strings, fields, find_type_path, parse_ron_value, validate_extras,
+    BlueprintPreloadAssets, ExtrasIssue, RonValue,
+};
+
+/// What is wrong with an asset, see [`lint_assets`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum LintIssueKind {
+    /// the gltf file could not be read or parsed
+    UnparsableGltf(String),
+    /// the `.meta.ron` file could not be read or parsed
+    UnparsableMeta(String),
+    /// a component (or its value) does not match the registry
+    InvalidComponent(ExtrasIssue),
+    /// a blueprint/level has no `.meta.ron` file next to it
+    MissingMetaFile,
+    /// an asset listed in a `.meta.ron` file or a `BlueprintAssets` component does not exist
+    MissingAsset(String),
+    /// a `BlueprintInfo` points to a blueprint that does not exist
+    DanglingBlueprint(String),
+    /// a `MaterialInfo` points to a material library that does not exist
+    MissingMaterialLibrary(String),
+    /// the material library exists, but could not be read or parsed
+    UnparsableMaterialLibrary { library: String, error: String },
+    /// the material library exists, but does not contain the material
+    MissingMaterial { library: String, material: String },
+}
+
+/// A single problem found by [`lint_assets`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintIssue {
+    /// path of the offending file, relative to the assets folder
+    pub file: PathBuf,
+    pub kind: LintIssueKind,
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.file.display())?;
+        match &self.kind {
+            LintIssueKind::UnparsableGltf(error) => write!(f, "unparsable gltf file ({error})"),
+            LintIssueKind::UnparsableMeta(error) => write!(f, "unparsable meta file ({error})"),
+            LintIssueKind::InvalidComponent(issue) => write!(f, "{issue}"),
+            LintIssueKind::MissingMetaFile => write!(f, "missing .meta.ron file"),
+            LintIssueKind::MissingAsset(path) => write!(f, "missing asset '{path}'"),
+            LintIssueKind::DanglingBlueprint(path) => write!(f, "missing blueprint '{path}'"),
+            LintIssueKind::MissingMaterialLibrary(path) => {
+                write!(f, "missing material library '{path}'")
+            }
+            LintIssueKind::UnparsableMaterialLibrary { library, error } => {
+                write!(f, "unparsable material library '{library}' ({error})")
+            }
+            LintIssueKind::MissingMaterial { library, material } => {
+                write!(f, "material '{material}' not found in '{library}'")
+            }
+        }
+    }
+}
+
+/// Checks all the gltf & `.meta.ron` files inside of an assets folder, without needing a Bevy app or renderer:
+/// - components in the gltf extras against the registry (`$defs` of an exported `registry.json`)
+/// - assets listed in `.meta.ron` files & `BlueprintAssets` components
+/// - missing `.meta.ron` files for blueprints & levels
+/// - paths of (nested) `BlueprintInfo` components
+/// - material libraries & materials referenced by `MaterialInfos` components
+pub fn lint_assets(assets_path: &Path, registry: &Value) -> Vec<LintIssue> {
+    let empty = Map::new();
+    let defs = registry
+        .get("$defs")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+
+    let mut files = vec![];
+    collect_files(assets_path, &mut files);
+    let relative = |path: &Path| path.strip_prefix(assets_path).unwrap_or(path).to_path_buf();
+    let exists = |asset_path: &str| assets_path.join(asset_path).is_file();
+
+    let mut issues = vec![];
+    // the material names of each library, `Err(None)` if the library does not exist
+    let mut material_libraries: HashMap<String, Result<HashSet<String>, Option<String>>> = HashMap::new();
+    let mut gltf_files = vec![];
+
+    for file in files.iter() {
+        let file_name = file.to_string_lossy();
+        if file_name.ends_with(".meta.ron") {
+            let meta = fs::read_to_string(file)
+                .map_err(|e| e.to_string())
+                .and_then(|content| {
+                    ron::from_str::<BlueprintPreloadAssets>(&content).map_err(|e| e.to_string())
+                });
+            match meta {
+                Ok(meta) => {
+                    for (_, asset) in meta.assets.iter() {
+                        if !exists(&asset.path) {
+                            issues.push(LintIssue {
+                                file: relative(file),
+                                kind: LintIssueKind::MissingAsset(asset.path.clone()),
+                            });
+                        }
+                    }
+                }
+                Err(error) => issues.push(LintIssue {
+                    file: relative(file),
+                    kind: LintIssueKind::UnparsableMeta(error),
+                }),
+            }
+            continue;
+        }
+        if !(file_name.ends_with(".glb") || file_name.ends_with(".gltf")) {
+            continue;
+        }
+
+        let gltf = match read_gltf_json(file) {
+            Ok(gltf) => gltf,
+            Err(error) => {
+                issues.push(LintIssue {
+                    file: relative(file),
+                    kind: LintIssueKind::UnparsableGltf(error),
+                });
+                continue;
+            }
+        };
+
+        let mut issue = |kind| {
+            issues.push(LintIssue {
+                file: relative(file),
+                kind,
+            });
+        };
+        for (node_path, extras) in gltf_extras(&gltf) {
+            for extras_issue in validate_extras(&node_path, &extras, defs) {
+                issue(LintIssueKind::InvalidComponent(extras_issue));
+            }
+            for reference in asset_references(&extras) {
+                match reference {
+                    AssetReference::Blueprint(path) if !exists(&path) => {
+                        issue(LintIssueKind::DanglingBlueprint(path));
+                    }
+                    AssetReference::Asset(path) if !exists(&path) => {
+                        issue(LintIssueKind::MissingAsset(path));
+                    }
+                    AssetReference::Material { library, material } => {
+                        let materials = material_libraries
+                            .entry(library.clone())
+                            .or_insert_with(|| {
+                                let library_path = assets_path.join(&library);
+                                if !library_path.is_file() {
+                                    return Err(None);
+                                }
+                                read_gltf_json(&library_path)
+                                    .map(|gltf| material_names(&gltf))
+                                    .map_err(Some)
+                            });
+                        match materials {
+                            Err(None) => issue(LintIssueKind::MissingMaterialLibrary(library)),
+                            Err(Some(error)) => issue(LintIssueKind::UnparsableMaterialLibrary {
+                                library,
+                                error: error.clone(),
+                            }),
+                            Ok(materials) if !materials.contains(&material) => {
+                                issue(LintIssueKind::MissingMaterial { library, material });
+                            }
+                            _ => {}
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        gltf_files.push(file);
+    }
+
+    // material libraries do not need a meta file, only blueprints & levels do
+    for file in gltf_files {
+        let relative_path = relative(file);
+        let asset_path = relative_path.to_string_lossy().replace('\\', "/");
+        if material_libraries.contains_key(&asset_path) {
+            continue;
+        }
+        if !Path::new(&blueprint_meta_path(&file.to_string_lossy())).is_file() {
+            issues.push(LintIssue {
+                file: relative_path,
+                kind: LintIssueKind::MissingMetaFile,
+            });
+        }
+    }
+    issues
+}
+
+/// A gltf file that uses some of the types given to [`assets_using_types`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AffectedAsset {
+    /// path of the file, relative to the assets folder
+    pub file: PathBuf,
+    /// the components (as written in the extras) that use the types, directly or through their fields
+    pub components: Vec<String>,
+}
+
+/// Lists the gltf files of an assets folder that contain components using any of the given types
+/// (ie the [`crate::RegistryDiff::affected_types`] of a registry change), directly or through their (nested) fields.
+/// `registry` is the export the assets were made with, used to resolve the component names & their fields
+pub fn assets_using_types(
+    assets_path: &Path,
+    registry: &Value,
+    type_paths: &BTreeSet<&str>,
+) -> Vec<AffectedAsset> {
+    let empty = Map::new();
+    let defs = registry
+        .get("$defs")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+
+    // all the types that contain one of the given types, no matter how deep
+    let mut using: HashSet<&str> = type_paths.iter().copied().collect();
+    loop {
+        let count = using.len();
+        for (type_path, def) in defs.iter() {
+            if fields(def).values().any(|field_type| using.contains(field_type.as_str())) {
+                using.insert(type_path);
+            }
+        }
+        if using.len() == count {
+            break;
+        }
+    }
+
+    let mut files = vec![];
+    collect_files(assets_path, &mut files);
+    let mut affected = vec![];
+    for file in files {
+        let file_name = file.to_string_lossy();
+        if !(file_name.ends_with(".glb") || file_name.ends_with(".gltf")) {
+            continue;
+        }
+        let Ok(gltf) = read_gltf_json(&file) else {
+            continue;
+        };
+        let mut components = BTreeSet::new();
+        for (_, extras) in gltf_extras(&gltf) {
+            for (name, _) in extras_to_component_ron_strings(&extras).unwrap_or_default() {
+                if find_type_path(&name, defs).is_some_and(|type_path| using.contains(type_path)) {
+                    components.insert(name.as_str().to_string());
+                }
+            }
+        }
+        if !components.is_empty() {
+            affected.push(AffectedAsset {
+                file: file.strip_prefix(assets_path).unwrap_or(&file).to_path_buf(),
+                components: components.into_iter().collect(),
+            });
+        }
+    }
+    affected
+}
+
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(path) else {
+        return;
+    };
+    let mut entries: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() {
+            collect_files(&entry, files);
+        } else {
+            files.push(entry);
+        }
+    }
+}
+
+/// reads the json part of a `.gltf` or `.glb` file
+pub(crate) fn read_gltf_json(path: &Path) -> Result<Value, String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    let json = if bytes.starts_with(b"glTF") {
+        // binary gltf: 12 bytes header, then the json chunk (length, type, data)
+        let chunk_length = bytes
+            .get(12..16)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+            .ok_or("truncated glb header")?;
+        bytes
+            .get(20..20 + chunk_length)
+            .ok_or("truncated glb json chunk")?
+    } else {
+        &bytes[..]
+    };
+    serde_json::from_slice(json).map_err(|e| e.to_string())
+}
+
+fn material_names(gltf: &Value) -> HashSet<String> {
+    gltf.get("materials")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|material| material.get("name").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect()
+}
+
+/// returns all the extras of a gltf file (scenes, nodes, meshes & materials), with a path for each of them
+fn gltf_extras(gltf: &Value) -> Vec<(String, String)> {
+    let items = |key: &str| -> &[Value] {
+        gltf.get(key)
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    };
+    let name = |item: &Value, index: usize| {
+        item.get("name")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| index.to_string())
+    };
+
+    let nodes = items("nodes");
+    let mut parents: HashMap<usize, usize> = HashMap::new();
+    for (index, node) in nodes.iter().enumerate() {
+        for child in node.get("children").and_then(Value::as_array).into_iter().flatten() {
+            if let Some(child) = child.as_u64() {
+                parents.insert(child as usize, index);
+            }
+        }
+    }
+    let node_path = |mut index: usize| {
+        let mut path = vec![name(&nodes[index], index)];
+        // invalid files can have cycles in their hierarchy
+        let mut visited = HashSet::from([index]);
+        while let Some(parent) = parents.get(&index) {
+            if !visited.insert(*parent) {
+                break;
+            }
+            index = *parent;
+            path.push(name(&nodes[index], index));
+        }
+        path.reverse();
+        path.join("/")
+    };
+
+    let mut extras = vec![];
+    for (kind, key) in [("scene", "scenes"), ("node", "nodes"), ("mesh", "meshes"), ("material", "materials")] {
+        for (index, item) in items(key).iter().enumerate() {
+            let Some(item_extras) = item.get("extras") else {
+                continue;
+            };
+            let path = if key == "nodes" {
+                node_path(index)
+            } else {
+                format!("{kind}:{}", name(item, index))
+            };
+            extras.push((path, item_extras.to_string()));
+        }
+    }
+    extras
+}
+
+enum AssetReference {
+    Blueprint(String),
+    Asset(String),
+    Material { library: String, material: String },
+}
+
+/// extracts the paths of blueprints, assets & materials used by the components in the given extras
+fn asset_references(extras: &str) -> Vec<AssetReference> {
+    let Ok(components) = extras_to_component_ron_strings(extras) else {
+        return vec![];
+    };
+    let mut references = vec![];
+    for (name, ron_string) in components {
+        let Ok(value) = parse_ron_value(&ron_string) else {
+            continue;
+        };
+        let short_name = name.as_str().rsplit("::").next().unwrap_or_default();
+        match short_name {
+            "BlueprintInfo" => {
+                if let Some(path) = string_field(&value, "path") {
+                    references.push(AssetReference::Blueprint(path));
+                }
+            }
+            "BlueprintAssets" => {
+                for asset in list_field(&value, "assets") {
+                    if let Some(path) = string_field(asset, "path") {
+                        references.push(AssetReference::Asset(path));
+                    }
+                }
+            }
+            "MaterialInfos" => {
+                let RonValue::Tuple(_, items) = &value else {
+                    continue;
+                };
+                let Some(RonValue::List(material_infos)) = items.first() else {
+                    continue;
+                };
+                for material_info in material_infos {
+                    if let (Some(library), Some(material)) = (
+                        string_field(material_info, "path"),
+                        string_field(material_info, "name"),
+                    ) {
+                        references.push(AssetReference::Material { library, material });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    references
+}
+
+fn field<'a>(value: &'a RonValue, field_name: &str) -> Option<&'a RonValue> {
+    let RonValue::Struct(_, fields) = value else {
+        return None;
+    };
+    fields
+        .iter()
+        .find(|(name, _)| name == field_name)
+        .map(|(_, value)| value)
+}
+
+fn string_field(value: &RonValue, field_name: &str) -> Option<String> {
+    match field(value, field_name) {
+        Some(RonValue::String(string)) => Some(string.clone()),
+        _ => None,
+    }
+}
+
+fn list_field<'a>(value: &'a RonValue, field_name: &str) -> &'a [RonValue] {
+    match field(value, field_name) {
+        Some(RonValue::List(items)) => items,
+        _ => &[],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    #[test]
+    fn meta_file_paths() {
+        assert_eq!(blueprint_meta_path("levels/World.glb"), "levels/World.meta.ron");
+        assert_eq!(blueprint_meta_path("blueprints/Car.gltf"), "blueprints/Car.meta.ron");
+        // only the extension of the file itself is replaced
+        assert_eq!(
+            blueprint_meta_path("exports.glb/my.gltf.files/Car.glb"),
+            "exports.glb/my.gltf.files/Car.meta.ron"
+        );
+    }
+
+    #[test]
+    fn cyclic_node_hierarchies() {
+        let gltf = json!({
+            "nodes": [
+                {"name": "A", "children": [1], "extras": {}},
+                {"name": "B", "children": [0], "extras": {}},
+            ]
+        });
+        let paths: Vec<String> = gltf_extras(&gltf).into_iter().map(|(path, _)| path).collect();
+        assert_eq!(paths, vec!["B/A".to_string(), "A/B".to_string()]);
+    }
+
+    #[test]
+    fn lint_fixture_folder() {
+        let assets = std::env::temp_dir().join(format!("blenvy_lint_{}", std::process::id()));
+        let write = |path: &str, content: &str| {
+            let path = assets.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        write(
+            "levels/World.gltf",
+            &json!({
+                "nodes": [
+                    {"name": "World", "children": [1, 2]},
+                    {"name": "Car", "extras": {"BlueprintInfo": "(name: \"Car\", path: \"blueprints/Car.gltf\")"}},
+                    {"name": "Truck", "extras": {
+                        "BlueprintInfo": "(name: \"Truck\", path: \"blueprints/Truck.gltf\")",
+                        "Unknown": "()",
+                    }},
+                ]
+            })
+            .to_string(),
+        );
+        write("levels/World.meta.ron", "(assets: [])");
+        // no meta file for this blueprint
+        write(
+            "blueprints/Car.gltf",
+            &json!({
+                "nodes": [{"name": "Car", "extras": {
+                    "MaterialInfos": "([(name: \"Metal\", path: \"materials/Broken.gltf\"), (name: \"Paint\", path: \"materials/Missing.gltf\")])",
+                }}]
+            })
+            .to_string(),
+        );
+        write("materials/Broken.gltf", "not a gltf file");
+        let registry = json!({
+            "$defs": {
+                "blenvy::BlueprintInfo": {"short_name": "BlueprintInfo"},
+                "blenvy::MaterialInfos": {"short_name": "MaterialInfos"},
+            }
+        });
+
+        let issues = lint_assets(&assets, &registry);
+        fs::remove_dir_all(&assets).unwrap();
+
+        let has_issue = |file: &str, check: &dyn Fn(&LintIssueKind) -> bool| {
+            issues.iter().any(|issue| issue.file == Path::new(file) && check(&issue.kind))
+        };
+        assert!(has_issue("levels/World.gltf", &|kind| {
+            *kind == LintIssueKind::DanglingBlueprint("blueprints/Truck.gltf".into())
+        }));
+        assert!(has_issue("levels/World.gltf", &|kind| {
+            *kind
+                == LintIssueKind::InvalidComponent(ExtrasIssue {
+                    node_path: "World/Truck".into(),
+                    component: "Unknown".into(),
+                    value_path: String::new(),
+                    kind: crate::ExtrasIssueKind::UnknownComponent,
+                })
+        }));
+        assert!(has_issue("blueprints/Car.gltf", &|kind| {
+            *kind == LintIssueKind::MissingMetaFile
+        }));
+        assert!(has_issue("blueprints/Car.gltf", &|kind| {
+            *kind == LintIssueKind::MissingMaterialLibrary("materials/Missing.gltf".into())
+        }));
+        assert!(has_issue("blueprints/Car.gltf", &|kind| {
+            matches!(kind, LintIssueKind::UnparsableMaterialLibrary { library, .. } if library == "materials/Broken.gltf")
+        }));
+        assert!(has_issue("materials/Broken.gltf", &|kind| {
+            matches!(kind, LintIssueKind::UnparsableGltf(_))
+        }));
+        assert_eq!(issues.len(), 6, "{issues:#?}");
+    }
+}
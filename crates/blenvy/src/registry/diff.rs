@@ -0,0 +1,527 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::schema_ref;
+
+/// What changed about a type between two registry exports, see [`diff_registries`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TypeChangeKind {
+    /// the type changed kind, ie from a tuple struct to a struct
+    TypeInfoChanged { from: String, to: String },
+    FieldAdded { field: String, field_type: String },
+    FieldRemoved { field: String, field_type: String },
+    FieldTypeChanged { field: String, from: String, to: String },
+    VariantAdded { variant: String },
+    VariantRemoved { variant: String },
+    /// the variant changed kind, ie from a unit variant to a tuple variant
+    VariantKindChanged { variant: String, from: String, to: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TypeChange {
+    pub type_path: String,
+    #[serde(flatten)]
+    pub kind: TypeChangeKind,
+}
+
+/// A type that was moved/renamed (same structure, different type path), see [`RegistryDiff::renamed`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TypeRename {
+    pub from: String,
+    pub to: String,
+}
+
+/// Machine readable report of the differences between two registry exports
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RegistryDiff {
+    /// the `x-blenvy-hash` of both exports, if present
+    pub old_hash: Option<String>,
+    pub new_hash: Option<String>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// removed types that have been added back with the same structure & a related name: the same short name,
+    /// or another name in the same module
+    pub renamed: Vec<TypeRename>,
+    /// removed & added types with the same structure but unrelated names, or several candidates:
+    /// they are still listed in `added` & `removed`
+    pub possible_renames: Vec<TypeRename>,
+    pub changed: Vec<TypeChange>,
+}
+
+impl RegistryDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.renamed.is_empty()
+            && self.changed.is_empty()
+    }
+
+    /// the (old) type paths of all the types that were removed, renamed or changed:
+    /// data using those types (ie in gltf extras) might need to be revisited, see [`crate::assets_using_types`]
+    pub fn affected_types(&self) -> BTreeSet<&str> {
+        self.removed
+            .iter()
+            .map(String::as_str)
+            .chain(self.renamed.iter().map(|rename| rename.from.as_str()))
+            .chain(self.changed.iter().map(|change| change.type_path.as_str()))
+            .collect()
+    }
+}
+
+/// Compares two registry exports (the json files written by the registry export)
+pub fn diff_registries(old: &Value, new: &Value) -> RegistryDiff {
+    let empty = Map::new();
+    let old_defs = old.get("$defs").and_then(Value::as_object).unwrap_or(&empty);
+    let new_defs = new.get("$defs").and_then(Value::as_object).unwrap_or(&empty);
+    let hash = |registry: &Value| {
+        registry
+            .get("x-blenvy-hash")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+
+    let mut removed: Vec<&String> = old_defs.keys().filter(|t| !new_defs.contains_key(*t)).collect();
+    let mut added: Vec<&String> = new_defs.keys().filter(|t| !old_defs.contains_key(*t)).collect();
+
+    // a removed & an added type with the same structure are considered a rename if their names are related
+    // (same short name, ie moved to another module/crate, or renamed within the same module):
+    // same structure pairs with unrelated names are only reported as possible renames
+    let mut renamed = vec![];
+    let mut possible_renames = vec![];
+    removed.retain(|from| {
+        let shape = type_shape(&old_defs[*from]);
+        let from_short_name = short_name(from, &old_defs[*from]);
+        // types without fields or variants all look the same: only their name can tell
+        let is_empty = shape.1.is_empty() && shape.2.is_empty();
+        let candidates: Vec<usize> = added
+            .iter()
+            .enumerate()
+            .filter(|(_, to)| type_shape(&new_defs[**to]) == shape)
+            .filter(|(_, to)| !is_empty || short_name(to, &new_defs[**to]) == from_short_name)
+            .map(|(index, _)| index)
+            .collect();
+        let same_short_name: Vec<usize> = candidates
+            .iter()
+            .copied()
+            .filter(|index| short_name(added[*index], &new_defs[added[*index]]) == from_short_name)
+            .collect();
+        let same_module: Vec<usize> = candidates
+            .iter()
+            .copied()
+            .filter(|index| module_path(added[*index]) == module_path(from))
+            .collect();
+        let index = match (same_short_name.as_slice(), same_module.as_slice()) {
+            ([index], _) | ([], [index]) => *index,
+            _ => {
+                possible_renames.extend(candidates.iter().map(|index| TypeRename {
+                    from: (*from).clone(),
+                    to: added[*index].clone(),
+                }));
+                return true;
+            }
+        };
+        renamed.push(TypeRename {
+            from: (*from).clone(),
+            to: added.remove(index).clone(),
+        });
+        false
+    });
+    // the targets of possible renames might have been taken by (later) actual renames
+    possible_renames.retain(|rename| added.contains(&&rename.to));
+    let renames: BTreeMap<&str, &str> = renamed
+        .iter()
+        .map(|rename| (rename.from.as_str(), rename.to.as_str()))
+        .collect();
+
+    let mut changed = vec![];
+    for (type_path, old_def) in old_defs.iter() {
+        if let Some(new_def) = new_defs.get(type_path) {
+            changed.extend(
+                diff_type(old_def, new_def, &renames)
+                    .into_iter()
+                    .map(|kind| TypeChange {
+                        type_path: type_path.clone(),
+                        kind,
+                    }),
+            );
+        }
+    }
+
+    RegistryDiff {
+        old_hash: hash(old),
+        new_hash: hash(new),
+        added: added.into_iter().cloned().collect(),
+        removed: removed.into_iter().cloned().collect(),
+        renamed,
+        possible_renames,
+        changed,
+    }
+}
+
+fn short_name<'a>(type_path: &'a str, def: &'a Value) -> &'a str {
+    def.get("short_name")
+        .and_then(Value::as_str)
+        .unwrap_or_else(|| module_path(type_path).map_or(type_path, |module| &type_path[module.len() + 2..]))
+}
+
+/// the module of a type, ie `my_game::player` for `my_game::player::Player<my_game::Stats>`
+fn module_path(type_path: &str) -> Option<&str> {
+    let end = type_path.find('<').unwrap_or(type_path.len());
+    type_path[..end].rsplit_once("::").map(|(module, _)| module)
+}
+
+/// the fields (or items, keys & values) of a type & the types they point to
+pub(crate) fn fields(def: &Value) -> BTreeMap<String, String> {
+    let mut fields = BTreeMap::new();
+    let mut insert = |name: String, schema: &Value| {
+        if let Some(field_type) = schema_ref(schema) {
+            fields.insert(name, field_type);
+        }
+    };
+    if let Some(properties) = def.get("properties").and_then(Value::as_object) {
+        for (name, schema) in properties {
+            insert(name.clone(), schema);
+        }
+    }
+    let prefix_items = def
+        .get("x-blenvy-prefixItems")
+        .or_else(|| def.get("prefixItems"))
+        .and_then(Value::as_array);
+    for (index, schema) in prefix_items.into_iter().flatten().enumerate() {
+        insert(index.to_string(), schema);
+    }
+    for (name, key) in [("[]", "items"), ("{key}", "keyType"), ("{value}", "valueType")] {
+        if let Some(schema) = def.get(key) {
+            insert(name.to_string(), schema);
+        }
+    }
+    fields
+}
+
+/// the variants of an enum & their definition (`None` for unit variants)
+fn variants(def: &Value) -> BTreeMap<String, Option<&Value>> {
+    let variants = def
+        .get("x-blenvy-variants")
+        .or_else(|| def.get("enum"))
+        .or_else(|| def.get("oneOf"))
+        .and_then(Value::as_array);
+    variants
+        .into_iter()
+        .flatten()
+        .filter_map(|variant| match variant {
+            Value::String(name) => Some((name.clone(), None)),
+            _ => {
+                let name = variant.get("long_name").and_then(Value::as_str)?;
+                Some((name.to_string(), variant.get("typeInfo").map(|_| variant)))
+            }
+        })
+        .collect()
+}
+
+fn variant_kind(variant: Option<&Value>) -> &str {
+    variant
+        .and_then(|variant| variant.get("typeInfo"))
+        .and_then(Value::as_str)
+        .unwrap_or("Unit")
+}
+
+fn type_info(def: &Value) -> &str {
+    def.get("typeInfo").and_then(Value::as_str).unwrap_or_default()
+}
+
+/// what identifies the structure of a type, regardless of its name
+fn type_shape(def: &Value) -> (String, BTreeMap<String, String>, Vec<(String, String)>) {
+    let variants = variants(def)
+        .into_iter()
+        .map(|(name, variant)| (name, variant_kind(variant).to_string()))
+        .collect();
+    (type_info(def).to_string(), fields(def), variants)
+}
+
+fn diff_fields(
+    prefix: &str,
+    old: BTreeMap<String, String>,
+    new: BTreeMap<String, String>,
+    renames: &BTreeMap<&str, &str>,
+    changes: &mut Vec<TypeChangeKind>,
+) {
+    for (field, field_type) in old.iter() {
+        let field_name = format!("{prefix}{field}");
+        match new.get(field) {
+            None => changes.push(TypeChangeKind::FieldRemoved {
+                field: field_name,
+                field_type: field_type.clone(),
+            }),
+            // renamed types are already reported on their own
+            Some(new_type)
+                if new_type != field_type
+                    && renames.get(field_type.as_str()) != Some(&new_type.as_str()) =>
+            {
+                changes.push(TypeChangeKind::FieldTypeChanged {
+                    field: field_name,
+                    from: field_type.clone(),
+                    to: new_type.clone(),
+                });
+            }
+            _ => {}
+        }
+    }
+    for (field, field_type) in new.iter().filter(|(field, _)| !old.contains_key(*field)) {
+        changes.push(TypeChangeKind::FieldAdded {
+            field: format!("{prefix}{field}"),
+            field_type: field_type.clone(),
+        });
+    }
+}
+
+fn diff_type(old: &Value, new: &Value, renames: &BTreeMap<&str, &str>) -> Vec<TypeChangeKind> {
+    let mut changes = vec![];
+    if type_info(old) != type_info(new) {
+        changes.push(TypeChangeKind::TypeInfoChanged {
+            from: type_info(old).to_string(),
+            to: type_info(new).to_string(),
+        });
+        return changes;
+    }
+    diff_fields("", fields(old), fields(new), renames, &mut changes);
+
+    let (old_variants, new_variants) = (variants(old), variants(new));
+    for (name, old_variant) in old_variants.iter() {
+        let Some(new_variant) = new_variants.get(name) else {
+            changes.push(TypeChangeKind::VariantRemoved {
+                variant: name.clone(),
+            });
+            continue;
+        };
+        let (old_kind, new_kind) = (variant_kind(*old_variant), variant_kind(*new_variant));
+        if old_kind != new_kind {
+            changes.push(TypeChangeKind::VariantKindChanged {
+                variant: name.clone(),
+                from: old_kind.to_string(),
+                to: new_kind.to_string(),
+            });
+        } else if let (Some(old_variant), Some(new_variant)) = (old_variant, new_variant) {
+            let prefix = format!("{name}.");
+            diff_fields(&prefix, fields(old_variant), fields(new_variant), renames, &mut changes);
+        }
+    }
+    for name in new_variants.keys().filter(|name| !old_variants.contains_key(*name)) {
+        changes.push(TypeChangeKind::VariantAdded {
+            variant: name.clone(),
+        });
+    }
+    changes
+}
+
+/// Stable (FNV-1a) hash of the type definitions of a registry export, stored as `x-blenvy-hash` in the export,
+/// so that tools can cheaply check if the registry changed
+pub fn registry_hash(defs: &Map<String, Value>) -> String {
+    let mut type_paths: Vec<&String> = defs.keys().collect();
+    type_paths.sort();
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for type_path in type_paths {
+        for byte in type_path.bytes().chain(defs[type_path].to_string().bytes()) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn struct_def(short_name: &str, fields: &[(&str, &str)]) -> Value {
+        let properties: Map<String, Value> = fields
+            .iter()
+            .map(|(name, field_type)| (name.to_string(), json!({ "$ref": crate::type_ref(field_type) })))
+            .collect();
+        json!({ "typeInfo": "Struct", "short_name": short_name, "properties": properties })
+    }
+
+    fn registry(defs: &[(&str, Value)]) -> Value {
+        let defs: Map<String, Value> = defs.iter().map(|(path, def)| (path.to_string(), def.clone())).collect();
+        json!({ "$defs": defs })
+    }
+
+    fn rename(from: &str, to: &str) -> TypeRename {
+        TypeRename {
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+    }
+
+    #[test]
+    fn identical_registries() {
+        let old = registry(&[("game::Health", struct_def("Health", &[("value", "f32")]))]);
+        let diff = diff_registries(&old, &old.clone());
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn added_and_removed() {
+        let old = registry(&[("game::Health", struct_def("Health", &[("value", "f32")]))]);
+        let new = registry(&[("game::Speed", struct_def("Speed", &[("x", "f32"), ("y", "f32")]))]);
+        let diff = diff_registries(&old, &new);
+        assert_eq!(diff.added, vec!["game::Speed"]);
+        assert_eq!(diff.removed, vec!["game::Health"]);
+        assert!(diff.renamed.is_empty());
+        assert!(diff.possible_renames.is_empty());
+        assert_eq!(diff.affected_types(), BTreeSet::from(["game::Health"]));
+    }
+
+    #[test]
+    fn changed() {
+        let old = registry(&[
+            ("game::Health", struct_def("Health", &[("value", "f32"), ("max", "f32")])),
+            ("game::State", json!({ "typeInfo": "Enum", "short_name": "State", "oneOf": ["Idle", "Running"] })),
+        ]);
+        let new = registry(&[
+            ("game::Health", struct_def("Health", &[("value", "u32"), ("regen", "f32")])),
+            ("game::State", json!({ "typeInfo": "Enum", "short_name": "State", "oneOf": ["Idle", "Jumping"] })),
+        ]);
+        let diff = diff_registries(&old, &new);
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.renamed.is_empty());
+        let changes: Vec<(&str, &TypeChangeKind)> = diff
+            .changed
+            .iter()
+            .map(|change| (change.type_path.as_str(), &change.kind))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (
+                    "game::Health",
+                    &TypeChangeKind::FieldRemoved {
+                        field: "max".into(),
+                        field_type: "f32".into()
+                    }
+                ),
+                (
+                    "game::Health",
+                    &TypeChangeKind::FieldTypeChanged {
+                        field: "value".into(),
+                        from: "f32".into(),
+                        to: "u32".into()
+                    }
+                ),
+                (
+                    "game::Health",
+                    &TypeChangeKind::FieldAdded {
+                        field: "regen".into(),
+                        field_type: "f32".into()
+                    }
+                ),
+                (
+                    "game::State",
+                    &TypeChangeKind::VariantRemoved {
+                        variant: "Running".into()
+                    }
+                ),
+                (
+                    "game::State",
+                    &TypeChangeKind::VariantAdded {
+                        variant: "Jumping".into()
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn type_info_changed() {
+        let old = registry(&[("game::Marker", json!({ "typeInfo": "Struct", "short_name": "Marker" }))]);
+        let new = registry(&[("game::Marker", json!({ "typeInfo": "TupleStruct", "short_name": "Marker" }))]);
+        let diff = diff_registries(&old, &new);
+        assert_eq!(
+            diff.changed[0].kind,
+            TypeChangeKind::TypeInfoChanged {
+                from: "Struct".into(),
+                to: "TupleStruct".into()
+            }
+        );
+    }
+
+    #[test]
+    fn renamed() {
+        let old = registry(&[
+            // moved to another module
+            ("game::Health", struct_def("Health", &[("value", "f32")])),
+            // renamed within its module
+            ("game::stats::Speed", struct_def("Speed", &[("x", "f32"), ("y", "f32")])),
+            ("game::Player", struct_def("Player", &[("health", "game::Health")])),
+            // fieldless types only match by name
+            ("game::Marker", json!({ "typeInfo": "Struct", "short_name": "Marker" })),
+        ]);
+        let new = registry(&[
+            ("game::combat::Health", struct_def("Health", &[("value", "f32")])),
+            ("game::stats::Velocity", struct_def("Velocity", &[("x", "f32"), ("y", "f32")])),
+            ("game::Player", struct_def("Player", &[("health", "game::combat::Health")])),
+            ("game::Tag", json!({ "typeInfo": "Struct", "short_name": "Tag" })),
+        ]);
+        let diff = diff_registries(&old, &new);
+        assert_eq!(
+            diff.renamed,
+            vec![
+                rename("game::Health", "game::combat::Health"),
+                rename("game::stats::Speed", "game::stats::Velocity"),
+            ]
+        );
+        assert_eq!(diff.added, vec!["game::Tag"]);
+        assert_eq!(diff.removed, vec!["game::Marker"]);
+        assert!(diff.possible_renames.is_empty());
+        // the field type follows the rename
+        assert!(diff.changed.is_empty());
+    }
+
+    #[test]
+    fn unrelated_names_are_possible_renames() {
+        let old = registry(&[("game::Health", struct_def("Health", &[("value", "f32")]))]);
+        let new = registry(&[("ui::Opacity", struct_def("Opacity", &[("value", "f32")]))]);
+        let diff = diff_registries(&old, &new);
+        assert!(diff.renamed.is_empty());
+        assert_eq!(diff.possible_renames, vec![rename("game::Health", "ui::Opacity")]);
+        assert_eq!(diff.added, vec!["ui::Opacity"]);
+        assert_eq!(diff.removed, vec!["game::Health"]);
+    }
+
+    #[test]
+    fn ambiguous_renames() {
+        let old = registry(&[("game::Speed", struct_def("Speed", &[("value", "f32")]))]);
+        let new = registry(&[
+            ("game::Velocity", struct_def("Velocity", &[("value", "f32")])),
+            ("game::Acceleration", struct_def("Acceleration", &[("value", "f32")])),
+        ]);
+        let diff = diff_registries(&old, &new);
+        assert!(diff.renamed.is_empty());
+        assert_eq!(diff.possible_renames.len(), 2);
+        assert!(diff.possible_renames.contains(&rename("game::Speed", "game::Velocity")));
+        assert!(diff.possible_renames.contains(&rename("game::Speed", "game::Acceleration")));
+        assert_eq!(diff.removed, vec!["game::Speed"]);
+
+        // the same short name settles it
+        let new = registry(&[
+            ("game::Velocity", struct_def("Velocity", &[("value", "f32")])),
+            ("game::movement::Speed", struct_def("Speed", &[("value", "f32")])),
+        ]);
+        let diff = diff_registries(&old, &new);
+        assert_eq!(diff.renamed, vec![rename("game::Speed", "game::movement::Speed")]);
+        assert!(diff.possible_renames.is_empty());
+        assert_eq!(diff.added, vec!["game::Velocity"]);
+    }
+
+    #[test]
+    fn short_names_and_modules() {
+        assert_eq!(module_path("game::player::Player"), Some("game::player"));
+        assert_eq!(module_path("game::Wrapper<other::Inner>"), Some("game"));
+        assert_eq!(module_path("f32"), None);
+        assert_eq!(short_name("game::Wrapper<other::Inner>", &json!({})), "Wrapper<other::Inner>");
+        assert_eq!(short_name("f32", &json!({})), "f32");
+    }
+}
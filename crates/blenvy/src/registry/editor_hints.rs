@@ -0,0 +1,172 @@
+use std::ops::{Range, RangeFrom, RangeInclusive, RangeToInclusive};
+
+use bevy::{
+    log::warn,
+    reflect::{attributes::CustomAttributes, Reflect},
+};
+use serde_json::{json, Value};
+
+// Custom reflect attributes that end up in the registry export, to give the Blender add-on hints on how to edit fields.
+// Numeric ranges use the standard range types directly: `#[reflect(@0.0..=1.0)]`, `#[reflect(@0..10)]`, `#[reflect(@0..)]`
+
+/// Increment used by the sliders / arrows in Blender: `#[reflect(@Step(0.1))]`
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct Step(pub f64);
+
+/// Unit of the value, shown in Blender: `#[reflect(@Unit("m"))]`
+/// `m`, `s`, `m/s`, `m/s²`, `kg`, `rad` & `W` are mapped to Blender's units, anything else is only shown in the tooltip
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct Unit(pub &'static str);
+
+/// Soft limits: the range of the sliders in Blender, while still allowing values outside of it to be typed in:
+/// `#[reflect(@SoftRange(0.0..=100.0))]`
+#[derive(Debug, Clone, PartialEq, Reflect)]
+pub struct SoftRange(pub RangeInclusive<f64>);
+
+/// Marks a string as the path of an asset, with an optional filter on the file names: `#[reflect(@AssetPathHint("*.glb;*.gltf"))]`
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct AssetPathHint(pub &'static str);
+
+/// minimum, maximum & whether the maximum is exclusive
+type Bounds = (Option<Value>, Option<Value>, bool);
+
+macro_rules! numeric_bounds {
+    ($value:expr, $($t:ty),*) => {
+        $(
+            if let Some(range) = $value.downcast_ref::<RangeInclusive<$t>>() {
+                return Some((Some(json!(range.start())), Some(json!(range.end())), false));
+            }
+            if let Some(range) = $value.downcast_ref::<Range<$t>>() {
+                return Some((Some(json!(range.start)), Some(json!(range.end)), true));
+            }
+            if let Some(range) = $value.downcast_ref::<RangeFrom<$t>>() {
+                return Some((Some(json!(range.start)), None, false));
+            }
+            if let Some(range) = $value.downcast_ref::<RangeToInclusive<$t>>() {
+                return Some((None, Some(json!(range.end)), false));
+            }
+        )*
+    };
+}
+
+fn bounds(value: &dyn Reflect) -> Option<Bounds> {
+    numeric_bounds!(value, f32, f64, u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+    None
+}
+
+/// ranges that no value can be in, ie `10.0..=0.0` or `5..5`
+fn is_empty_range(min: Option<&Value>, max: Option<&Value>, exclusive_max: bool) -> bool {
+    match (min.and_then(Value::as_f64), max.and_then(Value::as_f64)) {
+        (Some(min), Some(max)) => min > max || (exclusive_max && min == max),
+        _ => false,
+    }
+}
+
+/// adds the editor hints found in the custom attributes of a type, field or variant to its schema:
+/// ranges as `minimum`/`maximum`/`exclusiveMaximum`, the rest as `x-blenvy-*` keys
+pub(crate) fn add_editor_hints(mut schema: Value, attributes: &CustomAttributes) -> Value {
+    let Some(obj) = schema.as_object_mut() else {
+        return schema;
+    };
+    for (_, attribute) in attributes.iter() {
+        if let Some((min, max, exclusive_max)) = bounds(attribute) {
+            if is_empty_range(min.as_ref(), max.as_ref(), exclusive_max) {
+                warn!("Ignoring the empty range {:?} in the registry export", attribute);
+                continue;
+            }
+            if let Some(min) = min {
+                obj.insert("minimum".to_owned(), min);
+            }
+            if let Some(max) = max {
+                let key = if exclusive_max { "exclusiveMaximum" } else { "maximum" };
+                obj.insert(key.to_owned(), max);
+            }
+        } else if let Some(Step(step)) = attribute.downcast_ref::<Step>() {
+            obj.insert("x-blenvy-step".to_owned(), json!(step));
+        } else if let Some(Unit(unit)) = attribute.downcast_ref::<Unit>() {
+            obj.insert("x-blenvy-unit".to_owned(), json!(unit));
+        } else if let Some(SoftRange(range)) = attribute.downcast_ref::<SoftRange>() {
+            if range.is_empty() {
+                warn!("Ignoring the empty soft range {:?} in the registry export", range);
+                continue;
+            }
+            obj.insert("x-blenvy-soft-minimum".to_owned(), json!(range.start()));
+            obj.insert("x-blenvy-soft-maximum".to_owned(), json!(range.end()));
+        } else if let Some(AssetPathHint(filter)) = attribute.downcast_ref::<AssetPathHint>() {
+            obj.insert("x-blenvy-asset-path".to_owned(), json!(filter));
+        }
+    }
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::reflect::{GetTypeRegistration, TypeRegistry};
+
+    use super::*;
+    use crate::export_type;
+
+    #[derive(Reflect)]
+    struct Hints {
+        #[reflect(@Step(0.1), @Unit("m"), @SoftRange(0.0..=10.0))]
+        distance: f32,
+        #[reflect(@AssetPathHint("*.glb;*.gltf"))]
+        model: String,
+        #[reflect(@0.0..=1.0)]
+        ratio: f32,
+        #[reflect(@0..10)]
+        slot: u8,
+        #[reflect(@1_i32..)]
+        level: i32,
+        #[reflect(@..=100_u32)]
+        count: u32,
+        #[reflect(@10.0..=0.0, @5_u8..5, @SoftRange(3.0..=1.0))]
+        inverted: f32,
+    }
+
+    fn properties<T: GetTypeRegistration>() -> Value {
+        let mut types = TypeRegistry::default();
+        types.register::<T>();
+        let (_, schema) = export_type(types.get(std::any::TypeId::of::<T>()).unwrap(), &types);
+        schema["properties"].clone()
+    }
+
+    #[test]
+    fn blenvy_hints() {
+        let properties = properties::<Hints>();
+        assert_eq!(
+            properties["distance"],
+            json!({
+                "$ref": "#/$defs/f32",
+                "x-blenvy-step": 0.1,
+                "x-blenvy-unit": "m",
+                "x-blenvy-soft-minimum": 0.0,
+                "x-blenvy-soft-maximum": 10.0,
+            })
+        );
+        assert_eq!(
+            properties["model"],
+            json!({ "$ref": "#/$defs/alloc::string::String", "x-blenvy-asset-path": "*.glb;*.gltf" })
+        );
+    }
+
+    #[test]
+    fn range_bounds() {
+        let properties = properties::<Hints>();
+        assert_eq!(
+            properties["ratio"],
+            json!({ "$ref": "#/$defs/f32", "minimum": 0.0, "maximum": 1.0 })
+        );
+        assert_eq!(
+            properties["slot"],
+            json!({ "$ref": "#/$defs/u8", "minimum": 0, "exclusiveMaximum": 10 })
+        );
+        assert_eq!(properties["level"], json!({ "$ref": "#/$defs/i32", "minimum": 1 }));
+        assert_eq!(properties["count"], json!({ "$ref": "#/$defs/u32", "maximum": 100 }));
+    }
+
+    #[test]
+    fn empty_ranges_are_ignored() {
+        assert_eq!(properties::<Hints>()["inverted"], json!({ "$ref": "#/$defs/f32" }));
+    }
+}
@@ -1,69 +1,159 @@
-use crate::{AssetRoot, BlenvyConfig};
+use crate::{add_editor_hints, registry_hash, AssetRoot, BlenvyConfig, RegistryExportFilters};
 use bevy::{
-    log::info,
-    prelude::{AppTypeRegistry, ReflectComponent, ReflectResource, World},
-    reflect::{TypeInfo, TypeRegistration, VariantInfo},
+    asset::AssetPlugin,
+    log::{error, info},
+    prelude::{AppTypeRegistry, ReflectComponent, ReflectDefault, ReflectResource, World},
+    reflect::{
+        attributes::CustomAttributes,
+        serde::{SerializationData, TypedReflectSerializer},
+        ReflectSerialize, TypeInfo, TypeRegistration, TypeRegistry, VariantInfo,
+    },
 };
 use serde_json::{json, Map, Value};
-use std::{fs::File, path::Path};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
 
-pub fn export_types(world: &mut World) {
+/// Generates the registry export (a json schema document) of all the types allowed by the filters,
+/// and of all the types they (transitively) reference, so that every `$ref` of the export can be resolved
+pub fn registry_schema(types: &TypeRegistry, filters: &RegistryExportFilters) -> Value {
+    let mut schemas = types
+        .iter()
+        .filter(|reg| filters.is_allowed(reg))
+        .map(|reg| export_type(reg, types))
+        .collect::<Map<_, _>>();
+
+    let mut pending: Vec<String> = vec![];
+    for schema in schemas.values() {
+        collect_refs(schema, &mut pending);
+    }
+    while let Some(type_path) = pending.pop() {
+        if schemas.contains_key(&type_path) {
+            continue;
+        }
+        let Some(reg) = types.get_with_type_path(&type_path) else {
+            continue;
+        };
+        let (type_path, schema) = export_type(reg, types);
+        collect_refs(&schema, &mut pending);
+        schemas.insert(type_path, schema);
+    }
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "long_name": "bevy component registry schema",
+        "x-blenvy-hash": registry_hash(&schemas),
+        "$defs": schemas,
+    })
+}
+
+/// Writes the registry export to any writer: this does not need a running `App`,
+/// so it can be used from build scripts, CI, tests etc, see the `export_registry` binary of the components example
+pub fn export_to_writer(
+    types: &TypeRegistry,
+    filters: &RegistryExportFilters,
+    writer: impl Write,
+) -> serde_json::Result<()> {
+    serde_json::to_writer_pretty(writer, &registry_schema(types, filters))
+}
+
+/// Writes the registry export to a file, see [`export_to_writer`]
+pub fn export_to_file(
+    types: &TypeRegistry,
+    filters: &RegistryExportFilters,
+    path: impl AsRef<Path>,
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    export_to_writer(types, filters, &mut writer)?;
+    writer.flush()
+}
+
+impl BlenvyConfig {
+    /// the filters of the registry export, as configured on the `BlenvyPlugin`
+    pub fn registry_export_filters(&self) -> RegistryExportFilters {
+        RegistryExportFilters {
+            component_filter: self.registry_component_filter.clone(),
+            resource_filter: self.registry_resource_filter.clone(),
+            type_rules: self.registry_type_rules.clone(),
+        }
+    }
+}
+
+/// Writes the registry export of an app with the `BlenvyPlugin` (its types, filtered as configured on the plugin) to a file:
+/// the same output as the export done by the game at startup, without running the app
+pub fn export_app_registry(world: &World, path: impl AsRef<Path>) -> io::Result<()> {
     let config = world
         .get_resource::<BlenvyConfig>()
-        .expect("ExportComponentsConfig should exist at this stage");
-
-    let asset_root = world.resource::<AssetRoot>();
-    let registry_save_path = Path::join(&asset_root.0, &config.registry_save_path);
-    println!("Trying to create file at: {:?}", registry_save_path);
-    
-    let writer = File::create(registry_save_path).expect("should have created schema file");
+        .expect("the BlenvyPlugin should have been added to the app");
+    let types = world.resource::<AppTypeRegistry>().read();
+    export_to_file(&types, &config.registry_export_filters(), path)
+}
 
-    let components_to_filter_out = &config.registry_component_filter.clone();
-    let resources_to_filter_out = &config.registry_resource_filter.clone();
+/// where the game writes its registry export: the `registry_save_path` of the `BlenvyPlugin`, in the assets folder
+pub fn registry_export_path(world: &World) -> PathBuf {
+    let config = world
+        .get_resource::<BlenvyConfig>()
+        .expect("the BlenvyPlugin should have been added to the app");
+    // the `ExportRegistryPlugin` (which keeps track of the assets folder) is only added to debug builds
+    let asset_root = world
+        .get_resource::<AssetRoot>()
+        .map_or_else(|| PathBuf::from(AssetPlugin::default().file_path), |asset_root| asset_root.0.clone());
+    asset_root.join(&config.registry_save_path)
+}
 
-    let types = world.resource_mut::<AppTypeRegistry>();
-    let types = types.read();
-    let schemas = types
-        .iter()
-        .filter(|type_info| {
-            let type_id = type_info.type_id();
-            components_to_filter_out.is_allowed_by_id(type_id)
-                && resources_to_filter_out.is_allowed_by_id(type_id)
-        })
-        .map(export_type)
-        .collect::<Map<_, _>>();
+pub fn export_types(world: &mut World) {
+    let registry_save_path = registry_export_path(world);
+    match export_app_registry(world, &registry_save_path) {
+        Ok(()) => info!("Done exporting registry schema to {:?}", registry_save_path),
+        Err(error) => error!(
+            "Failed to export registry schema to {:?}: {}",
+            registry_save_path, error
+        ),
+    }
+}
 
-    serde_json::to_writer_pretty(
-        writer,
-        &json!({
-            "$schema": "https://json-schema.org/draft/2020-12/schema",
-            "long_name": "bevy component registry schema",
-            "$defs": schemas,
-        }),
-    )
-    .expect("valid json");
+/// generates the schema definitions (the `$defs` part of the registry export) of all the registered types
+pub fn registry_type_defs(types: &TypeRegistry) -> Map<String, Value> {
+    types.iter().map(|reg| export_type(reg, types)).collect()
+}
 
-    info!("Done exporting registry schema");
+/// doc comments are only available with the `documentation` feature
+#[cfg(feature = "documentation")]
+macro_rules! docs {
+    ($info:expr) => {
+        $info.docs()
+    };
+}
+#[cfg(not(feature = "documentation"))]
+macro_rules! docs {
+    ($info:expr) => {{
+        let _ = &$info;
+        None::<&str>
+    }};
 }
 
-pub fn export_type(reg: &TypeRegistration) -> (String, Value) {
+pub fn export_type(reg: &TypeRegistration, types: &TypeRegistry) -> (String, Value) {
     let t = reg.type_info();
+    let default = default_value(reg, types);
     let binding = t.type_path_table();
     let short_name = binding.short_path();
     let mut schema = match t {
         TypeInfo::Struct(info) => {
             let properties = info
                 .iter()
-                .enumerate()
-                .map(|(idx, field)| {
-                    (
-                        field.name().to_owned(),
-                        add_min_max(json!({ "type": typ(field.type_path()) }), reg, idx, None),
-                    )
+                .map(|field| {
+                    let mut field_schema = add_editor_hints(typ(field.type_path()), field.custom_attributes());
+                    describe(&mut field_schema, docs!(field));
+                    if let Some(field_default) = default.as_ref().and_then(|d| d.get(field.name())) {
+                        field_schema["default"] = field_default.clone();
+                    }
+                    (field.name().to_owned(), field_schema)
                 })
                 .collect::<Map<_, _>>();
 
-            json!({
+            let mut schema = json!({
                 "type": "object",
                 "typeInfo": "Struct",
                 "long_name": t.type_path(),
@@ -74,7 +164,13 @@ pub fn export_type(reg: &TypeRegistration) -> (String, Value) {
                     .filter(|field| !field.type_path().starts_with("core::option::Option"))
                     .map(|field| field.name())
                     .collect::<Vec<_>>(),
-            })
+            });
+            // types with their own serde implementation (ie `Vec3` => `[1.0, 2.0, 3.0]`) are not necessarily
+            // serialized as objects: the properties then only apply if they are
+            if reg.data::<ReflectSerialize>().is_some() {
+                schema.as_object_mut().unwrap().remove("type");
+            }
+            schema
         }
         TypeInfo::Enum(info) => {
             let simple = info
@@ -85,97 +181,143 @@ pub fn export_type(reg: &TypeRegistration) -> (String, Value) {
                     "type": "string",
                     "typeInfo": "Enum",
                     "long_name": t.type_path(),
-                    "oneOf": info
+                    "enum": info
                         .iter()
-                        .map(|variant| match variant {
-                            VariantInfo::Unit(v) => v.name(),
-                            _ => unreachable!(),
-                        })
+                        .map(|variant| variant.name())
                         .collect::<Vec<_>>(),
                 })
             } else {
+                // the Blender add-on builds its UI from the variant list in `x-blenvy-variants`,
+                // while `oneOf` (or `anyOf` for options) describes the serialized form of the enum
                 let variants = info
-                .iter()
-                .enumerate()
-                .map(|(field_idx, variant)| match variant {
-                    //let binding = t.type_path_table();
-                    //let short_name = binding.short_path();
-                    VariantInfo::Struct(v) => json!({
-                        "type": "object",
-                        "typeInfo": "Struct",
-                        "long_name": v.name(),
-                        "short_name": v.name().split("::").last().unwrap_or(v.name()),
-                        "properties": v
-                            .iter()
-                            .enumerate()
-                            .map(|(variant_idx, field)| (field.name().to_owned(), add_min_max(json!({"type": typ(field.type_path()), "long_name": field.name()}), reg, field_idx, Some(variant_idx))))
-                            .collect::<Map<_, _>>(),
-                        "additionalProperties": false,
-                        "required": v
-                            .iter()
-                            .filter(|field| !field.type_path().starts_with("core::option::Option"))
-                            .map(|field| field.name())
-                            .collect::<Vec<_>>(),
-                    }),
-                    VariantInfo::Tuple(v) => json!({
-                        "type": "array",
-                        "typeInfo": "Tuple",
-                        "long_name": v.name(),
-                        "short_name":v.name(),
-                        "prefixItems": v
+                    .iter()
+                    .map(|variant| match variant {
+                        VariantInfo::Struct(v) => json!({
+                            "type": "object",
+                            "typeInfo": "Struct",
+                            "long_name": v.name(),
+                            "short_name": v.name().split("::").last().unwrap_or(v.name()),
+                            "properties": v
+                                .iter()
+                                .map(|field| {
+                                    let mut field_schema = add_editor_hints(typ(field.type_path()), field.custom_attributes());
+                                    field_schema["long_name"] = field.name().into();
+                                    describe(&mut field_schema, docs!(field));
+                                    (field.name().to_owned(), field_schema)
+                                })
+                                .collect::<Map<_, _>>(),
+                            "additionalProperties": false,
+                            "required": v
+                                .iter()
+                                .filter(|field| !field.type_path().starts_with("core::option::Option"))
+                                .map(|field| field.name())
+                                .collect::<Vec<_>>(),
+                        }),
+                        VariantInfo::Tuple(v) => json!({
+                            "type": "array",
+                            "typeInfo": "Tuple",
+                            "long_name": v.name(),
+                            "short_name": v.name(),
+                            "prefixItems": v
+                                .iter()
+                                .map(|field| {
+                                    let mut field_schema = add_editor_hints(typ(field.type_path()), field.custom_attributes());
+                                    describe(&mut field_schema, docs!(field));
+                                    field_schema
+                                })
+                                .collect::<Vec<_>>(),
+                            "minItems": v.field_len(),
+                            "items": false,
+                        }),
+                        VariantInfo::Unit(v) => json!({
+                            "long_name": v.name(),
+                        }),
+                    })
+                    .zip(info.iter())
+                    .map(|(variant_schema, variant)| {
+                        let mut variant_schema = add_editor_hints(variant_schema, variant.custom_attributes());
+                        describe(&mut variant_schema, docs!(variant));
+                        variant_schema
+                    })
+                    .collect::<Vec<_>>();
+
+                if is_option(t) {
+                    let some = info
+                        .variant("Some")
+                        .and_then(|variant| match variant {
+                            VariantInfo::Tuple(v) => v.field_at(0),
+                            _ => None,
+                        })
+                        .map(|field| typ(field.type_path()))
+                        .unwrap_or(json!({}));
+                    json!({
+                        "typeInfo": "Enum",
+                        "long_name": t.type_path(),
+                        "anyOf": [{ "type": "null" }, some],
+                        "x-blenvy-variants": variants,
+                    })
+                } else {
+                    json!({
+                        "typeInfo": "Enum",
+                        "long_name": t.type_path(),
+                        "oneOf": variants
                             .iter()
-                            .enumerate()
-                            .map(|(variant_idx, field)| add_min_max(json!({"type": typ(field.type_path())}), reg, field_idx, Some(variant_idx)))
+                            .map(serialized_variant)
                             .collect::<Vec<_>>(),
-                        "items": false,
-                    }),
-                    VariantInfo::Unit(v) => json!({
-                        "long_name": v.name(),
-                    }),
+                        "x-blenvy-variants": variants,
+                    })
+                }
+            }
+        }
+        TypeInfo::TupleStruct(info) => {
+            let prefix_items = info
+                .iter()
+                .map(|field| {
+                    let mut field_schema = add_editor_hints(typ(field.type_path()), field.custom_attributes());
+                    describe(&mut field_schema, docs!(field));
+                    field_schema
                 })
                 .collect::<Vec<_>>();
-
+            // newtypes are serialized as their inner value
+            if info.field_len() == 1 && reg.data::<SerializationData>().is_none() {
+                let mut schema = prefix_items[0].clone();
+                schema["long_name"] = t.type_path().into();
+                schema["typeInfo"] = "TupleStruct".into();
+                schema["x-blenvy-prefixItems"] = prefix_items.into();
+                schema
+            } else {
                 json!({
-                    "type": "object",
-                    "typeInfo": "Enum",
                     "long_name": t.type_path(),
-                    "oneOf": variants,
+                    "type": "array",
+                    "typeInfo": "TupleStruct",
+                    "prefixItems": prefix_items,
+                    "minItems": info.field_len(),
+                    "items": false,
                 })
             }
         }
-        TypeInfo::TupleStruct(info) => json!({
+        TypeInfo::List(info) => json!({
             "long_name": t.type_path(),
             "type": "array",
-            "typeInfo": "TupleStruct",
-            "prefixItems": info
-                .iter()
-                .enumerate()
-                .map(|(idx, field)| add_min_max(json!({"type": typ(field.type_path())}), reg, idx, None))
-                .collect::<Vec<_>>(),
-            "items": false,
+            "typeInfo": "List",
+            "items": typ(info.item_ty().path()),
         }),
-        TypeInfo::List(info) => {
-            json!({
-                "long_name": t.type_path(),
-                "type": "array",
-                "typeInfo": "List",
-               // Changed from info.item_type_path_table to type_path_table
-                "items": json!({"type": typ(info.type_path_table().path())}),
-            })
-        }
         TypeInfo::Array(info) => json!({
             "long_name": t.type_path(),
             "type": "array",
             "typeInfo": "Array",
-            // Changed from info.item_type_path_table to type_path_table
-            "items": json!({"type": typ(info.type_path_table().path())}),
+            "items": typ(info.item_ty().path()),
+            "minItems": info.capacity(),
+            "maxItems": info.capacity(),
         }),
         TypeInfo::Map(info) => json!({
             "long_name": t.type_path(),
             "type": "object",
             "typeInfo": "Map",
-            "valueType": json!({"type": typ(info.type_path_table().path())}),
-            "keyType": json!({"type": typ(info.type_path_table().path())}),
+            // keys are serialized as strings in json, so only the values can be checked
+            "additionalProperties": typ(info.value_ty().path()),
+            "valueType": typ(info.value_ty().path()),
+            "keyType": typ(info.key_ty().path()),
         }),
         TypeInfo::Tuple(info) => json!({
             "long_name": t.type_path(),
@@ -183,21 +325,22 @@ pub fn export_type(reg: &TypeRegistration) -> (String, Value) {
             "typeInfo": "Tuple",
             "prefixItems": info
                 .iter()
-                .enumerate()
-                .map(|(idx, field)| add_min_max(json!({"type": typ(field.type_path())}), reg, idx, None))
+                .map(|field| add_editor_hints(typ(field.type_path()), field.custom_attributes()))
                 .collect::<Vec<_>>(),
+            "minItems": info.field_len(),
             "items": false,
         }),
-        TypeInfo::Opaque(info) => json!({
-            "long_name": t.type_path(),
-            "type": map_json_type(info.type_path()),
-            "typeInfo": "Value",
-        }),
+        TypeInfo::Opaque(info) => {
+            let mut schema = opaque_schema(info.type_path());
+            schema["long_name"] = t.type_path().into();
+            schema["typeInfo"] = "Value".into();
+            schema
+        }
         TypeInfo::Set(info) => json!({
             "long_name": t.type_path(),
             "type": "array",
             "typeInfo": "Set",
-            "items": json!({"type": typ(info.type_path_table().path())}),
+            "items": typ(info.value_ty().path()),
             "uniqueItems": true, // Enforce uniqueness for Set
         }),
     };
@@ -215,72 +358,441 @@ pub fn export_type(reg: &TypeRegistration) -> (String, Value) {
         .unwrap()
         .insert("short_name".to_owned(), short_name.into());
 
+    if let Some(attributes) = type_attributes(t) {
+        schema = add_editor_hints(schema, attributes);
+    }
+    describe(&mut schema, docs!(t));
+    if let Some(default) = default {
+        schema["default"] = default;
+    }
+
     (t.type_path().to_owned(), schema)
 }
 
+fn type_attributes(t: &TypeInfo) -> Option<&CustomAttributes> {
+    match t {
+        TypeInfo::Struct(info) => Some(info.custom_attributes()),
+        TypeInfo::TupleStruct(info) => Some(info.custom_attributes()),
+        TypeInfo::Enum(info) => Some(info.custom_attributes()),
+        _ => None,
+    }
+}
+
+/// the (serialized) value of `Default::default()` for types registered with `#[reflect(Default)]`
+fn default_value(reg: &TypeRegistration, types: &TypeRegistry) -> Option<Value> {
+    let default = reg.data::<ReflectDefault>()?.default();
+    serde_json::to_value(TypedReflectSerializer::new(default.as_partial_reflect(), types)).ok()
+}
+
+/// adds the (cleaned up) doc comment of a type, field or variant as the `description` of its schema
+fn describe(schema: &mut Value, docs: Option<&str>) {
+    let Some(docs) = docs else {
+        return;
+    };
+    let description = docs.lines().map(str::trim).collect::<Vec<_>>().join("\n");
+    if !description.trim().is_empty() {
+        schema["description"] = description.trim().into();
+    }
+}
+
+/// the type paths of all the `$ref`s inside of a schema
+fn collect_refs(schema: &Value, refs: &mut Vec<String>) {
+    match schema {
+        Value::Object(obj) => {
+            for (key, value) in obj {
+                match (key.as_str(), value) {
+                    ("$ref", Value::String(reference)) => refs.extend(type_path_from_ref(reference)),
+                    _ => collect_refs(value, refs),
+                }
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                collect_refs(value, refs);
+            }
+        }
+        _ => {}
+    }
+}
+
 fn typ(t: &str) -> Value {
-    json!({ "$ref": format!("#/$defs/{t}") })
+    json!({ "$ref": type_ref(t) })
+}
+
+/// builds the `$ref` pointing to the schema definition of a type: type paths contain characters
+/// (`<`, `>`, spaces, `[`...) that are not allowed in uri fragments, so they are percent encoded
+pub fn type_ref(type_path: &str) -> String {
+    let mut reference = String::from("#/$defs/");
+    for byte in type_path.bytes() {
+        match byte {
+            b'~' => reference.push_str("~0"),
+            b'/' => reference.push_str("~1"),
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b':' | b'(' | b')'
+            | b',' | b';' | b'=' | b'&' | b'!' | b'*' | b'+' | b'\'' | b'@' | b'$' => {
+                reference.push(byte as char);
+            }
+            _ => reference.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    reference
+}
+
+/// inverse of [`type_ref`]: returns the type path a `$ref` points to
+pub fn type_path_from_ref(reference: &str) -> Option<String> {
+    let encoded = reference.strip_prefix("#/$defs/")?;
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut input = encoded.bytes();
+    while let Some(byte) = input.next() {
+        if byte == b'%' {
+            let hex = [input.next()?, input.next()?];
+            let hex = std::str::from_utf8(&hex).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    let decoded = String::from_utf8(bytes).ok()?;
+    Some(decoded.replace("~1", "/").replace("~0", "~"))
+}
+
+fn is_option(t: &TypeInfo) -> bool {
+    let path_table = t.type_path_table();
+    path_table.module_path() == Some("core::option") && path_table.ident() == Some("Option")
+}
+
+/// schema of the serialized (externally tagged) form of an enum variant
+fn serialized_variant(variant: &Value) -> Value {
+    let name = variant["long_name"].as_str().unwrap_or_default();
+    let mut content = match variant.get("typeInfo").and_then(Value::as_str) {
+        Some("Struct") => json!({
+            "type": "object",
+            "properties": variant["properties"],
+            "additionalProperties": false,
+            "required": variant["required"],
+        }),
+        // newtype variants are serialized as their inner value
+        Some("Tuple") if variant["prefixItems"].as_array().is_some_and(|items| items.len() == 1) => {
+            variant["prefixItems"][0].clone()
+        }
+        Some("Tuple") => json!({
+            "type": "array",
+            "prefixItems": variant["prefixItems"],
+            "minItems": variant["minItems"],
+            "items": false,
+        }),
+        // unit variants are serialized as their name
+        _ => {
+            let mut unit = json!({ "const": name });
+            if let Some(description) = variant.get("description") {
+                unit["description"] = description.clone();
+            }
+            return unit;
+        }
+    };
+    if let Some(description) = variant.get("description") {
+        content["description"] = description.clone();
+    }
+    json!({
+        "type": "object",
+        "properties": { name: content },
+        "required": [name],
+        "additionalProperties": false,
+    })
 }
 
-fn map_json_type(t: &str) -> Value {
+fn opaque_schema(t: &str) -> Value {
     match t {
-        "bool" => "boolean",
-        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" => "uint",
-        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => "int",
-        "f32" | "f64" => "float",
-        "char" | "str" | "alloc::string::String" => "string",
-        _ => "object",
+        "bool" => json!({ "type": "boolean" }),
+        "u8" => integer(u8::MIN, u8::MAX),
+        "u16" => integer(u16::MIN, u16::MAX),
+        "u32" => integer(u32::MIN, u32::MAX),
+        "u64" => integer(u64::MIN, u64::MAX),
+        "usize" => integer(usize::MIN as u64, usize::MAX as u64),
+        "i8" => integer(i8::MIN, i8::MAX),
+        "i16" => integer(i16::MIN, i16::MAX),
+        "i32" => integer(i32::MIN, i32::MAX),
+        "i64" => integer(i64::MIN, i64::MAX),
+        "isize" => integer(isize::MIN as i64, isize::MAX as i64),
+        // json numbers cannot represent the full 128 bits range without losing precision
+        "u128" => json!({ "type": "integer", "minimum": 0 }),
+        "i128" => json!({ "type": "integer" }),
+        "f32" | "f64" => json!({ "type": "number" }),
+        "char" => json!({ "type": "string", "minLength": 1, "maxLength": 1 }),
+        "str" | "alloc::string::String" | "alloc::borrow::Cow<str>" | "std::path::PathBuf" => {
+            json!({ "type": "string" })
+        }
+        // other opaque types have custom serializations that we know nothing about
+        _ => json!({}),
     }
-    .into()
 }
 
-fn add_min_max(
-    mut val: Value,
-    reg: &TypeRegistration,
-    field_index: usize,
-    variant_index: Option<usize>,
-) -> Value {
-    /*fn get_min_max(
-        reg: &TypeRegistration,
-        field_index: usize,
-        variant_index: Option<usize>,
-    ) -> Option<(Option<f32>, Option<f32>)> {
-        use bevy_inspector_egui::inspector_options::{
-            std_options::NumberOptions, ReflectInspectorOptions, Target,
-        };
+fn integer(min: impl Into<Value>, max: impl Into<Value>) -> Value {
+    json!({
+        "type": "integer",
+        "minimum": min.into(),
+        "maximum": max.into(),
+    })
+}
 
-        reg.data::<ReflectInspectorOptions>()
-            .and_then(|ReflectInspectorOptions(o)| {
-                o.get(if let Some(variant_index) = variant_index {
-                    Target::VariantField {
-                        variant_index,
-                        field_index,
-                    }
-                } else {
-                    Target::Field(field_index)
-                })
+#[cfg(test)]
+mod tests {
+    use bevy::reflect::{GetTypeRegistration, Reflect};
+
+    use super::*;
+
+    #[derive(Reflect)]
+    struct Point {
+        x: f32,
+        label: Option<String>,
+    }
+
+    #[derive(Reflect)]
+    struct Pair(f32, u8);
+
+    #[derive(Reflect)]
+    struct Meters(f32);
+
+    #[derive(Reflect)]
+    enum Direction {
+        North,
+        South,
+    }
+
+    #[derive(Reflect)]
+    enum Shape {
+        Circle(f32),
+        Rect { width: f32, height: Option<f32> },
+        Empty,
+    }
+
+    const MODULE: &str = "blenvy::registry::export_types::tests";
+
+    fn schema<T: GetTypeRegistration>() -> Value {
+        let mut types = TypeRegistry::default();
+        types.register::<T>();
+        let (type_path, schema) = export_type(types.get(std::any::TypeId::of::<T>()).unwrap(), &types);
+        assert_eq!(schema["long_name"], type_path.as_str());
+        schema
+    }
+
+    #[test]
+    fn type_refs_round_trip() {
+        for type_path in [
+            "f32",
+            "bevy_transform::components::transform::Transform",
+            "alloc::vec::Vec<(f32, glam::Vec3)>",
+            "core::option::Option<alloc::string::String>",
+            "bevy_utils::hashbrown::HashMap<alloc::string::String, [f32; 3]>",
+            "my_game/with/slashes",
+            "tilde~type",
+            "both~1/escapes~0",
+            "with spaces & %percent",
+            "unicode::Typé",
+        ] {
+            let reference = type_ref(type_path);
+            assert!(reference.starts_with("#/$defs/"), "{reference}");
+            let fragment = &reference["#/$defs/".len()..];
+            assert!(
+                !fragment.contains(['<', '>', ' ', '/', '[', ']', '"', '#', '{', '}']),
+                "{type_path} => {reference}"
+            );
+            assert_eq!(type_path_from_ref(&reference).as_deref(), Some(type_path), "{reference}");
+        }
+        assert_eq!(type_ref("a/b~c<d>"), "#/$defs/a~1b~0c%3Cd%3E");
+        assert_eq!(type_path_from_ref("f32"), None);
+        assert_eq!(type_path_from_ref("#/$defs/broken%3"), None);
+    }
+
+    #[test]
+    fn struct_schema() {
+        assert_eq!(
+            schema::<Point>(),
+            json!({
+                "type": "object",
+                "typeInfo": "Struct",
+                "long_name": format!("{MODULE}::Point"),
+                "short_name": "Point",
+                "properties": {
+                    "x": { "$ref": "#/$defs/f32" },
+                    "label": { "$ref": type_ref("core::option::Option<alloc::string::String>") },
+                },
+                "additionalProperties": false,
+                "required": ["x"],
+                "isComponent": false,
+                "isResource": false,
             })
-            .and_then(|o| o.downcast_ref::<NumberOptions<f32>>())
-            .map(|num| (num.min, num.max))
-    }*/
-
-    fn get_min_max(
-        _reg: &TypeRegistration,
-        _field_index: usize,
-        _variant_index: Option<usize>,
-    ) -> Option<(Option<f32>, Option<f32>)> {
-        None
+        );
     }
 
-    let Some((min, max)) = get_min_max(reg, field_index, variant_index) else {
-        return val;
-    };
-    let obj = val.as_object_mut().unwrap();
-    if let Some(min) = min {
-        obj.insert("minimum".to_owned(), min.into());
+    #[test]
+    fn tuple_struct_schema() {
+        assert_eq!(
+            schema::<Pair>(),
+            json!({
+                "type": "array",
+                "typeInfo": "TupleStruct",
+                "long_name": format!("{MODULE}::Pair"),
+                "short_name": "Pair",
+                "prefixItems": [{ "$ref": "#/$defs/f32" }, { "$ref": "#/$defs/u8" }],
+                "minItems": 2,
+                "items": false,
+                "isComponent": false,
+                "isResource": false,
+            })
+        );
+    }
+
+    #[test]
+    fn newtype_schema() {
+        // serialized as its inner value
+        assert_eq!(
+            schema::<Meters>(),
+            json!({
+                "$ref": "#/$defs/f32",
+                "typeInfo": "TupleStruct",
+                "long_name": format!("{MODULE}::Meters"),
+                "short_name": "Meters",
+                "x-blenvy-prefixItems": [{ "$ref": "#/$defs/f32" }],
+                "isComponent": false,
+                "isResource": false,
+            })
+        );
     }
-    if let Some(max) = max {
-        obj.insert("maximum".to_owned(), max.into());
+
+    #[test]
+    fn unit_enum_schema() {
+        assert_eq!(
+            schema::<Direction>(),
+            json!({
+                "type": "string",
+                "typeInfo": "Enum",
+                "long_name": format!("{MODULE}::Direction"),
+                "short_name": "Direction",
+                "enum": ["North", "South"],
+                "isComponent": false,
+                "isResource": false,
+            })
+        );
+    }
+
+    #[test]
+    fn complex_enum_schema() {
+        let schema = schema::<Shape>();
+        assert_eq!(schema["typeInfo"], "Enum");
+        assert_eq!(schema["short_name"], "Shape");
+        assert_eq!(
+            schema["x-blenvy-variants"],
+            json!([
+                {
+                    "type": "array",
+                    "typeInfo": "Tuple",
+                    "long_name": "Circle",
+                    "short_name": "Circle",
+                    "prefixItems": [{ "$ref": "#/$defs/f32" }],
+                    "minItems": 1,
+                    "items": false,
+                },
+                {
+                    "type": "object",
+                    "typeInfo": "Struct",
+                    "long_name": "Rect",
+                    "short_name": "Rect",
+                    "properties": {
+                        "width": { "$ref": "#/$defs/f32", "long_name": "width" },
+                        "height": { "$ref": type_ref("core::option::Option<f32>"), "long_name": "height" },
+                    },
+                    "additionalProperties": false,
+                    "required": ["width"],
+                },
+                { "long_name": "Empty" },
+            ])
+        );
+        // the serialized (externally tagged) forms
+        assert_eq!(
+            schema["oneOf"],
+            json!([
+                {
+                    "type": "object",
+                    "properties": { "Circle": { "$ref": "#/$defs/f32" } },
+                    "required": ["Circle"],
+                    "additionalProperties": false,
+                },
+                {
+                    "type": "object",
+                    "properties": {
+                        "Rect": {
+                            "type": "object",
+                            "properties": {
+                                "width": { "$ref": "#/$defs/f32", "long_name": "width" },
+                                "height": { "$ref": type_ref("core::option::Option<f32>"), "long_name": "height" },
+                            },
+                            "additionalProperties": false,
+                            "required": ["width"],
+                        },
+                    },
+                    "required": ["Rect"],
+                    "additionalProperties": false,
+                },
+                { "const": "Empty" },
+            ])
+        );
+    }
+
+    /// A pool of health points,
+    ///   that regenerates over time
+    #[derive(Reflect)]
+    #[reflect(Default)]
+    struct Health {
+        /// maximum health
+        max: f32,
+        regen: f32,
+    }
+
+    impl Default for Health {
+        fn default() -> Self {
+            Health { max: 100.0, regen: 0.5 }
+        }
+    }
+
+    #[derive(Reflect)]
+    enum Movement {
+        /// on foot
+        Walk,
+        Fly(f32),
+    }
+
+    #[test]
+    fn default_values() {
+        let health = schema::<Health>();
+        assert_eq!(health["default"], json!({ "max": 100.0, "regen": 0.5 }));
+        assert_eq!(health["properties"]["max"]["default"], json!(100.0));
+        assert_eq!(health["properties"]["regen"]["default"], json!(0.5));
+        // without `#[reflect(Default)]`
+        assert_eq!(schema::<Point>().get("default"), None);
+    }
+
+    #[cfg(feature = "documentation")]
+    #[test]
+    fn descriptions() {
+        let health = schema::<Health>();
+        assert_eq!(
+            health["description"],
+            "A pool of health points,\nthat regenerates over time"
+        );
+        assert_eq!(health["properties"]["max"]["description"], "maximum health");
+        assert_eq!(health["properties"]["regen"].get("description"), None);
+
+        let movement = schema::<Movement>();
+        assert_eq!(movement["x-blenvy-variants"][0]["description"], "on foot");
+        assert_eq!(movement["oneOf"][0], json!({ "const": "Walk", "description": "on foot" }));
+        assert_eq!(movement["x-blenvy-variants"][1].get("description"), None);
+    }
+
+    #[cfg(not(feature = "documentation"))]
+    #[test]
+    fn no_descriptions() {
+        for schema in [schema::<Health>(), schema::<Movement>()] {
+            assert!(!schema.to_string().contains("description"), "{schema}");
+        }
     }
-    val
 }
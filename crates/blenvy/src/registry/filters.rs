@@ -0,0 +1,279 @@
+use std::any::TypeId;
+
+use bevy::{
+    prelude::{ReflectComponent, ReflectResource},
+    reflect::{TypeInfo, TypeRegistration},
+    scene::SceneFilter,
+};
+
+use crate::ReflectBlenvyExport;
+
+/// Which of the registered types end up in the registry export
+#[derive(Debug, Clone, Default)]
+pub struct RegistryExportFilters {
+    pub component_filter: SceneFilter,
+    pub resource_filter: SceneFilter,
+    pub type_rules: RegistryTypeRules,
+}
+
+impl RegistryExportFilters {
+    pub fn is_allowed(&self, reg: &TypeRegistration) -> bool {
+        let type_id = reg.type_id();
+        self.component_filter.is_allowed_by_id(type_id)
+            && self.resource_filter.is_allowed_by_id(type_id)
+            && self.type_rules.is_allowed(reg)
+    }
+}
+
+/// Include/exclude rules based on the type paths & type data of the registered types, to keep the registry export
+/// (and the component picker in Blender) small, without having to list every single type like with a `SceneFilter`.
+/// The types used by the fields of the exported types are always exported too, so that all `$ref`s can be resolved.
+///
+/// ```
+/// # use blenvy::RegistryTypeRules;
+/// let rules = RegistryTypeRules::default()
+///     .include("my_game")
+///     .include("blenvy::*")
+///     .exclude("my_game::debug")
+///     .components_and_resources_only();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RegistryTypeRules {
+    /// if not empty, only the types whose path matches one of these patterns are exported
+    pub include: Vec<String>,
+    /// types whose path matches one of these patterns are never exported (unless they are needed by another type)
+    pub exclude: Vec<String>,
+    /// only export types registered with `#[reflect(Component)]` or `#[reflect(Resource)]`
+    pub components_and_resources_only: bool,
+    /// only export types flagged with [`ReflectBlenvyExport`], see [`crate::BlenvyRegistrationApp::register_blenvy_type`]
+    pub blenvy_export_only: bool,
+    /// only export types with this custom attribute, ie `#[reflect(@ExportToBlender)]`
+    pub marker_attribute: Option<TypeId>,
+}
+
+impl RegistryTypeRules {
+    /// Adds an include pattern: either a crate/module path (`my_game`, `my_game::enemies`) matching everything inside it,
+    /// or a glob where `*` matches any sequence of characters (`bevy_*`, `my_game::*Settings`)
+    pub fn include(mut self, pattern: impl Into<String>) -> Self {
+        self.include.push(pattern.into());
+        self
+    }
+
+    /// Adds an exclude pattern, see [`RegistryTypeRules::include`] for the syntax
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.exclude.push(pattern.into());
+        self
+    }
+
+    pub fn components_and_resources_only(mut self) -> Self {
+        self.components_and_resources_only = true;
+        self
+    }
+
+    pub fn blenvy_export_only(mut self) -> Self {
+        self.blenvy_export_only = true;
+        self
+    }
+
+    /// Only export the types marked with the `T` custom attribute: `#[reflect(@T)]`
+    pub fn with_marker_attribute<T: 'static>(mut self) -> Self {
+        self.marker_attribute = Some(TypeId::of::<T>());
+        self
+    }
+
+    pub fn is_allowed(&self, reg: &TypeRegistration) -> bool {
+        let type_path = reg.type_info().type_path();
+        if !self.include.is_empty()
+            && !self.include.iter().any(|pattern| path_matches(pattern, type_path))
+        {
+            return false;
+        }
+        if self.exclude.iter().any(|pattern| path_matches(pattern, type_path)) {
+            return false;
+        }
+        if self.components_and_resources_only
+            && reg.data::<ReflectComponent>().is_none()
+            && reg.data::<ReflectResource>().is_none()
+        {
+            return false;
+        }
+        if self.blenvy_export_only && reg.data::<ReflectBlenvyExport>().is_none() {
+            return false;
+        }
+        match self.marker_attribute {
+            Some(marker) => has_attribute(reg.type_info(), marker),
+            None => true,
+        }
+    }
+}
+
+fn has_attribute(t: &TypeInfo, attribute: TypeId) -> bool {
+    match t {
+        TypeInfo::Struct(info) => info.custom_attributes().contains_by_id(attribute),
+        TypeInfo::TupleStruct(info) => info.custom_attributes().contains_by_id(attribute),
+        TypeInfo::Enum(info) => info.custom_attributes().contains_by_id(attribute),
+        _ => false,
+    }
+}
+
+/// patterns without a `*` match the path itself & everything inside it (`my_game` matches `my_game::Player` but not `my_game_utils::Foo`),
+/// otherwise `*` matches any sequence of characters, including `::`
+fn path_matches(pattern: &str, type_path: &str) -> bool {
+    if !pattern.contains('*') {
+        return type_path
+            .strip_prefix(pattern)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"));
+    }
+    glob_matches(pattern.as_bytes(), type_path.as_bytes())
+}
+
+fn glob_matches(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // position of the last `*` in the pattern & of the text it is currently matching up to
+    let mut backtrack = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{prelude::*, reflect::TypeRegistry};
+
+    use super::*;
+    use crate::{register_blenvy_type_in, registry_schema};
+
+    #[derive(Reflect)]
+    struct ExportToBlender;
+
+    #[derive(Component, Reflect)]
+    #[reflect(Component, @ExportToBlender)]
+    struct Player {
+        stats: Stats,
+    }
+
+    #[derive(Reflect)]
+    struct Stats {
+        health: f32,
+    }
+
+    #[derive(Component, Reflect)]
+    #[reflect(Component)]
+    struct DebugOverlay;
+
+    const MODULE: &str = "blenvy::registry::filters::tests";
+
+    fn registry() -> TypeRegistry {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Player>();
+        registry.register::<DebugOverlay>();
+        registry
+    }
+
+    fn allowed(rules: &RegistryTypeRules, registry: &TypeRegistry) -> Vec<&'static str> {
+        let mut allowed: Vec<&'static str> = registry
+            .iter()
+            .filter(|reg| reg.type_info().type_path().starts_with(MODULE) && rules.is_allowed(reg))
+            .map(|reg| reg.type_info().type_path_table().short_path())
+            .collect();
+        allowed.sort();
+        allowed
+    }
+
+    #[test]
+    fn module_patterns() {
+        assert!(path_matches("my_game", "my_game::Player"));
+        assert!(path_matches("my_game", "my_game::enemies::Boss"));
+        assert!(path_matches("my_game::Player", "my_game::Player"));
+        assert!(path_matches("my_game::enemies", "my_game::enemies::Boss"));
+        assert!(!path_matches("my_game", "my_game_other::Player"));
+        assert!(!path_matches("my_game::enemies", "my_game::enemies_utils::Boss"));
+        assert!(!path_matches("my_game::Player", "my_game::PlayerStats"));
+        assert!(!path_matches("game", "my_game::Player"));
+    }
+
+    #[test]
+    fn glob_patterns() {
+        assert!(path_matches("bevy_*", "bevy_transform::components::Transform"));
+        assert!(path_matches("bevy_*", "bevy_render::view::Visibility"));
+        assert!(!path_matches("bevy_*", "bevy::prelude::Transform"));
+        assert!(path_matches("my_game::*Settings", "my_game::AudioSettings"));
+        // `*` also matches `::`
+        assert!(path_matches("my_game::*Settings", "my_game::audio::AudioSettings"));
+        assert!(path_matches("my_game::*Settings", "my_game::Settings"));
+        assert!(!path_matches("my_game::*Settings", "my_game::SettingsMenu"));
+        assert!(!path_matches("my_game::*Settings", "other::AudioSettings"));
+        assert!(path_matches("*::Player*", "my_game::PlayerStats"));
+        assert!(path_matches("*", "anything::at::all"));
+        assert!(path_matches("a*b*c", "aXbYbZc"));
+        assert!(!path_matches("a*b*c", "aXbYcZ"));
+    }
+
+    #[test]
+    fn include_and_exclude_rules() {
+        let registry = registry();
+        assert_eq!(
+            allowed(&RegistryTypeRules::default(), &registry),
+            vec!["DebugOverlay", "Player", "Stats"]
+        );
+        let rules = RegistryTypeRules::default()
+            .include(MODULE)
+            .exclude(format!("{MODULE}::Debug*"));
+        assert_eq!(allowed(&rules, &registry), vec!["Player", "Stats"]);
+        let rules = RegistryTypeRules::default().include("other_crate");
+        assert_eq!(allowed(&rules, &registry), Vec::<&str>::new());
+        let rules = RegistryTypeRules::default().components_and_resources_only();
+        assert_eq!(allowed(&rules, &registry), vec!["DebugOverlay", "Player"]);
+    }
+
+    #[test]
+    fn excluded_types_needed_by_exported_types() {
+        let registry = registry();
+        let filters = RegistryExportFilters {
+            type_rules: RegistryTypeRules::default()
+                .include(MODULE)
+                .exclude(format!("{MODULE}::Stats"))
+                .exclude(format!("{MODULE}::DebugOverlay")),
+            ..default()
+        };
+        let schema = registry_schema(&registry, &filters);
+        let defs = schema["$defs"].as_object().unwrap();
+        assert!(defs.contains_key(&format!("{MODULE}::Player")));
+        // used by a field of `Player`, as are the types of its own fields
+        assert!(defs.contains_key(&format!("{MODULE}::Stats")));
+        assert!(defs.contains_key("f32"));
+        assert!(!defs.contains_key(&format!("{MODULE}::DebugOverlay")));
+    }
+
+    #[test]
+    fn blenvy_export_only() {
+        let mut registry = registry();
+        let rules = RegistryTypeRules::default().blenvy_export_only();
+        assert_eq!(allowed(&rules, &registry), Vec::<&str>::new());
+        register_blenvy_type_in::<DebugOverlay>(&mut registry);
+        assert_eq!(allowed(&rules, &registry), vec!["DebugOverlay"]);
+    }
+
+    #[test]
+    fn marker_attribute() {
+        let registry = registry();
+        let rules = RegistryTypeRules::default().with_marker_attribute::<ExportToBlender>();
+        assert_eq!(allowed(&rules, &registry), vec!["Player"]);
+        // combined with the other rules
+        let rules = rules.exclude(MODULE);
+        assert_eq!(allowed(&rules, &registry), Vec::<&str>::new());
+    }
+}
@@ -3,6 +3,24 @@ use std::path::PathBuf;
 pub mod export_types;
 pub use export_types::*;
 
+pub mod diff;
+pub use diff::*;
+
+pub mod filters;
+pub use filters::*;
+
+pub mod editor_hints;
+pub use editor_hints::*;
+
+pub mod registration;
+pub use registration::*;
+
+pub mod ron_value;
+pub use ron_value::*;
+
+pub mod validation;
+pub use validation::*;
+
 use bevy::{
     app::Startup,
     asset::AssetPlugin,
@@ -36,6 +54,7 @@ fn export_registry(blenvy_config: Res<BlenvyConfig>) -> bool {
 impl Plugin for ExportRegistryPlugin {
     fn build(&self, app: &mut App) {
         app.register_asset_root()
+            .register_derived_blenvy_types()
             .add_systems(Startup, export_types.run_if(export_registry));
     }
 }
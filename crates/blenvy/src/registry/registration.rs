@@ -0,0 +1,170 @@
+use std::any::TypeId;
+
+use bevy::{
+    log::warn,
+    prelude::{App, AppTypeRegistry},
+    reflect::{FromType, GetTypeRegistration, TypeInfo, TypeRegistry, VariantInfo},
+};
+
+/// Type data flagging a type as part of the Blenvy registry export (ie something that should show up in Blender)
+/// You can either add it by hand with `#[reflect(Component, BlenvyExport)]`, or let
+/// [`BlenvyRegistrationApp::register_blenvy_type`] insert it for you (along with all the field types)
+#[derive(Clone, Debug, Default)]
+pub struct ReflectBlenvyExport;
+
+impl<T> FromType<T> for ReflectBlenvyExport {
+    fn from_type() -> Self {
+        ReflectBlenvyExport
+    }
+}
+
+/// the registration function of a type deriving `BlenvyComponent`, collected with `inventory`
+#[doc(hidden)]
+pub struct DerivedBlenvyRegistration(pub fn(&mut TypeRegistry));
+
+inventory::collect!(DerivedBlenvyRegistration);
+
+/// used by `#[derive(BlenvyComponent)]`
+#[doc(hidden)]
+pub use inventory as __inventory;
+
+/// Registers all the types deriving [`BlenvyComponent`](crate::BlenvyComponent) (and their field types) in the given registry,
+/// & flags them with [`ReflectBlenvyExport`], see [`BlenvyRegistrationApp::register_derived_blenvy_types`]
+pub fn register_derived_blenvy_types_in(registry: &mut TypeRegistry) {
+    let mut registered = 0;
+    for registration in inventory::iter::<DerivedBlenvyRegistration> {
+        (registration.0)(registry);
+        registered += 1;
+    }
+    // blenvy's own types derive it: if nothing was collected, the platform / linker dropped the registrations
+    if registered == 0 {
+        warn!("No types deriving BlenvyComponent were found, register them with `register_blenvy_type` instead");
+    }
+}
+
+/// registers `T` and all of its (transitive) field types, and flags all of them with [`ReflectBlenvyExport`]
+#[doc(hidden)]
+pub fn register_blenvy_type_in<T: GetTypeRegistration>(registry: &mut TypeRegistry) {
+    // this also registers all the type dependencies (field types etc)
+    registry.register::<T>();
+    flag_for_export(registry, TypeId::of::<T>());
+}
+
+/// Extension trait to register the types used in Blender in a single call:
+/// ```
+/// # use bevy::prelude::*;
+/// # use blenvy::BlenvyRegistrationApp;
+/// #[derive(Component, Reflect, Default)]
+/// #[reflect(Component)]
+/// struct Health {
+///     max: f32,
+///     regen: Vec<(f32, f32)>,
+/// }
+///
+/// App::new().register_blenvy_type::<Health>(); // also registers & flags Vec<(f32, f32)>, (f32, f32) etc
+/// ```
+/// Or derive [`BlenvyComponent`](crate::BlenvyComponent) to have them registered automatically by the `BlueprintsPlugin`
+/// (or the `ExportRegistryPlugin`)
+pub trait BlenvyRegistrationApp {
+    /// registers `T` and all of its (transitive) field types in the `AppTypeRegistry`,
+    /// and flags all of them with [`ReflectBlenvyExport`]
+    fn register_blenvy_type<T: GetTypeRegistration>(&mut self) -> &mut Self;
+
+    /// registers all the types deriving `BlenvyComponent`: done by the `BlueprintsPlugin` & the `ExportRegistryPlugin`,
+    /// call it yourself if you use neither (ie to export the registry from a tool)
+    fn register_derived_blenvy_types(&mut self) -> &mut Self;
+}
+
+impl BlenvyRegistrationApp for App {
+    fn register_blenvy_type<T: GetTypeRegistration>(&mut self) -> &mut Self {
+        let registry = self.world().resource::<AppTypeRegistry>().clone();
+        register_blenvy_type_in::<T>(&mut registry.write());
+        self
+    }
+
+    fn register_derived_blenvy_types(&mut self) -> &mut Self {
+        let registry = self.world().resource::<AppTypeRegistry>().clone();
+        register_derived_blenvy_types_in(&mut registry.write());
+        self
+    }
+}
+
+/// flags the given type & all of its (registered) field types for export
+fn flag_for_export(registry: &mut TypeRegistry, type_id: TypeId) {
+    let mut pending = vec![type_id];
+    while let Some(type_id) = pending.pop() {
+        let Some(registration) = registry.get_mut(type_id) else {
+            continue;
+        };
+        if registration.data::<ReflectBlenvyExport>().is_some() {
+            continue;
+        }
+        registration.insert(ReflectBlenvyExport);
+        pending.extend(field_type_ids(registration.type_info()));
+    }
+}
+
+/// returns the type ids of all the direct "children" of a type: struct fields, enum variant fields, list items, map keys & values etc
+pub(crate) fn field_type_ids(type_info: &TypeInfo) -> Vec<TypeId> {
+    match type_info {
+        TypeInfo::Struct(info) => info.iter().map(|field| field.type_id()).collect(),
+        TypeInfo::TupleStruct(info) => info.iter().map(|field| field.type_id()).collect(),
+        TypeInfo::Tuple(info) => info.iter().map(|field| field.type_id()).collect(),
+        TypeInfo::Enum(info) => info
+            .iter()
+            .flat_map(|variant| match variant {
+                VariantInfo::Struct(v) => v.iter().map(|field| field.type_id()).collect(),
+                VariantInfo::Tuple(v) => v.iter().map(|field| field.type_id()).collect(),
+                VariantInfo::Unit(_) => vec![],
+            })
+            .collect(),
+        TypeInfo::List(info) => vec![info.item_ty().id()],
+        TypeInfo::Array(info) => vec![info.item_ty().id()],
+        TypeInfo::Map(info) => vec![info.key_ty().id(), info.value_ty().id()],
+        TypeInfo::Set(info) => vec![info.value_ty().id()],
+        TypeInfo::Opaque(_) => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::*;
+    use crate::BlenvyComponent;
+
+    #[derive(Component, Reflect, Default, BlenvyComponent)]
+    #[reflect(Component)]
+    struct DerivedHealth {
+        max: f32,
+        regen: Vec<(f32, f32)>,
+    }
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component)]
+    struct NotDerived;
+
+    #[test]
+    fn derived_types_get_registered_without_explicit_calls() {
+        let mut app = App::new();
+        app.register_derived_blenvy_types();
+
+        let registry = app.world().resource::<AppTypeRegistry>().read();
+        let registration = registry
+            .get(TypeId::of::<DerivedHealth>())
+            .expect("derived type should be registered");
+        assert!(registration.data::<ReflectBlenvyExport>().is_some());
+        assert!(registration.data::<ReflectComponent>().is_some());
+        // field types are registered & flagged too
+        let field_registration = registry
+            .get(TypeId::of::<Vec<(f32, f32)>>())
+            .expect("field type should be registered");
+        assert!(field_registration.data::<ReflectBlenvyExport>().is_some());
+
+        assert!(registry.get(TypeId::of::<NotDerived>()).is_none());
+        // blenvy's own types use it too
+        assert!(registry
+            .get(TypeId::of::<crate::BlueprintInfo>())
+            .is_some_and(|registration| registration.data::<ReflectBlenvyExport>().is_some()));
+    }
+}
@@ -0,0 +1,538 @@
+//! Minimal RON parser used to validate gltf extras against the registry schema.
+//!
+//! `ron::Value` cannot be used for this: it drops the names of structs & enum variants (`Idle` becomes `Unit`,
+//! `Sword(damage: 1.5)` a map & `Some(Idle)` an option), and validating enums needs those names.
+//! So this only covers the subset of RON written by the Blender add-on: bools, decimal numbers, strings, chars,
+//! identifiers, (named) tuples & structs, lists & maps.
+//! Anything else (comments, raw strings, hexadecimal numbers etc) is rejected here, see [`crate::validate_extras`]
+//! for how such values are handled
+
+/// RON value tree: unlike `ron::Value`, it keeps struct & enum variant names around
+#[derive(Debug, Clone, PartialEq)]
+pub enum RonValue {
+    Bool(bool),
+    Number {
+        value: f64,
+        integer: bool,
+    },
+    String(String),
+    Char(char),
+    /// a bare identifier: unit struct, unit enum variant, `None` etc
+    Ident(String),
+    /// `(a, b)` or `Name(a, b)` (includes `()` and `Some(a)`)
+    Tuple(Option<String>, Vec<RonValue>),
+    /// `(a: 1, b: 2)` or `Name(a: 1, b: 2)`
+    Struct(Option<String>, Vec<(String, RonValue)>),
+    List(Vec<RonValue>),
+    Map(Vec<(RonValue, RonValue)>),
+}
+
+impl RonValue {
+    /// short human readable name of the kind of value, for error reporting
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RonValue::Bool(_) => "bool",
+            RonValue::Number { integer: true, .. } => "integer",
+            RonValue::Number { .. } => "float",
+            RonValue::String(_) => "string",
+            RonValue::Char(_) => "char",
+            RonValue::Ident(_) => "identifier",
+            RonValue::Tuple(..) => "tuple",
+            RonValue::Struct(..) => "struct",
+            RonValue::List(_) => "list",
+            RonValue::Map(_) => "map",
+        }
+    }
+}
+
+/// parses a RON string into a [`RonValue`] tree
+pub fn parse_ron_value(input: &str) -> Result<RonValue, String> {
+    let mut parser = Parser {
+        chars: input.chars().collect(),
+        pos: 0,
+    };
+    let value = parser.value()?;
+    parser.skip_ws();
+    if parser.pos < parser.chars.len() {
+        return Err(parser.error("trailing characters"));
+    }
+    Ok(value)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn error(&self, message: &str) -> String {
+        format!("{message} at position {}", self.pos)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.peek(), Some(c) if c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_ws();
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{expected}'")))
+        }
+    }
+
+    /// consumes the given char if it is next (ignoring whitespace)
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_ws();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn value(&mut self) -> Result<RonValue, String> {
+        self.skip_ws();
+        match self.peek() {
+            None => Err(self.error("unexpected end of input")),
+            Some('"') => self.string().map(RonValue::String),
+            Some('\'') => self.char_literal(),
+            Some('[') => self.list(),
+            Some('{') => self.map(),
+            Some('(') => self.parenthesized(None),
+            Some(c) if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => self.number(),
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let ident = self.identifier();
+                match ident.as_str() {
+                    "true" => return Ok(RonValue::Bool(true)),
+                    "false" => return Ok(RonValue::Bool(false)),
+                    "inf" => {
+                        return Ok(RonValue::Number {
+                            value: f64::INFINITY,
+                            integer: false,
+                        })
+                    }
+                    "NaN" => {
+                        return Ok(RonValue::Number {
+                            value: f64::NAN,
+                            integer: false,
+                        })
+                    }
+                    _ => {}
+                }
+                self.skip_ws();
+                if self.peek() == Some('(') {
+                    self.parenthesized(Some(ident))
+                } else {
+                    Ok(RonValue::Ident(ident))
+                }
+            }
+            Some(c) => Err(self.error(&format!("unexpected character '{c}'"))),
+        }
+    }
+
+    fn identifier(&mut self) -> String {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if c.is_alphanumeric() || c == '_') {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn number(&mut self) -> Result<RonValue, String> {
+        let start = self.pos;
+        if matches!(self.peek(), Some('-') | Some('+')) {
+            self.pos += 1;
+        }
+        if self.peek() == Some('i') {
+            // -inf / +inf
+            self.identifier();
+            let value = if self.chars[start] == '-' {
+                f64::NEG_INFINITY
+            } else {
+                f64::INFINITY
+            };
+            return Ok(RonValue::Number {
+                value,
+                integer: false,
+            });
+        }
+        let mut integer = true;
+        while let Some(c) = self.peek() {
+            match c {
+                '0'..='9' | '_' => {}
+                '.' | 'e' | 'E' => integer = false,
+                '-' | '+' if matches!(self.chars[self.pos - 1], 'e' | 'E') => {}
+                _ => break,
+            }
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos]
+            .iter()
+            .filter(|c| **c != '_')
+            .collect();
+        text.parse::<f64>()
+            .map(|value| RonValue::Number { value, integer })
+            .map_err(|_| self.error(&format!("invalid number '{text}'")))
+    }
+
+    fn escaped(&mut self) -> Result<char, String> {
+        self.pos += 1;
+        let c = self
+            .peek()
+            .ok_or_else(|| self.error("unterminated escape"))?;
+        self.pos += 1;
+        Ok(match c {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            'u' => {
+                self.expect('{')?;
+                let start = self.pos;
+                while matches!(self.peek(), Some(c) if c != '}') {
+                    self.pos += 1;
+                }
+                let hex: String = self.chars[start..self.pos].iter().collect();
+                self.expect('}')?;
+                u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| self.error("invalid unicode escape"))?
+            }
+            '\\' | '"' | '\'' => c,
+            _ => return Err(self.error(&format!("unsupported escape '\\{c}'"))),
+        })
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.pos += 1;
+        let mut result = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(result);
+                }
+                Some('\\') => result.push(self.escaped()?),
+                Some(c) => {
+                    result.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    fn char_literal(&mut self) -> Result<RonValue, String> {
+        self.pos += 1;
+        let c = match self.peek() {
+            Some('\\') => self.escaped()?,
+            Some(c) => {
+                self.pos += 1;
+                c
+            }
+            None => return Err(self.error("unterminated char")),
+        };
+        if self.peek() != Some('\'') {
+            return Err(self.error("unterminated char"));
+        }
+        self.pos += 1;
+        Ok(RonValue::Char(c))
+    }
+
+    fn list(&mut self) -> Result<RonValue, String> {
+        self.pos += 1;
+        let mut items = vec![];
+        while !self.eat(']') {
+            items.push(self.value()?);
+            if !self.eat(',') {
+                self.expect(']')?;
+                break;
+            }
+        }
+        Ok(RonValue::List(items))
+    }
+
+    fn map(&mut self) -> Result<RonValue, String> {
+        self.pos += 1;
+        let mut entries = vec![];
+        while !self.eat('}') {
+            let key = self.value()?;
+            self.expect(':')?;
+            let value = self.value()?;
+            entries.push((key, value));
+            if !self.eat(',') {
+                self.expect('}')?;
+                break;
+            }
+        }
+        Ok(RonValue::Map(entries))
+    }
+
+    /// either a tuple or a struct, depending on whether the first element is `identifier:`
+    fn parenthesized(&mut self, name: Option<String>) -> Result<RonValue, String> {
+        self.pos += 1;
+        if self.eat(')') {
+            return Ok(RonValue::Tuple(name, vec![]));
+        }
+        let checkpoint = self.pos;
+        self.skip_ws();
+        let is_struct = if matches!(self.peek(), Some(c) if c.is_alphabetic() || c == '_') {
+            self.identifier();
+            self.eat(':')
+        } else {
+            false
+        };
+        self.pos = checkpoint;
+
+        if is_struct {
+            let mut fields = vec![];
+            while !self.eat(')') {
+                self.skip_ws();
+                let field_name = self.identifier();
+                if !field_name.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+                    return Err(self.error("expected field name"));
+                }
+                self.expect(':')?;
+                fields.push((field_name, self.value()?));
+                if !self.eat(',') {
+                    self.expect(')')?;
+                    break;
+                }
+            }
+            Ok(RonValue::Struct(name, fields))
+        } else {
+            let mut items = vec![];
+            while !self.eat(')') {
+                items.push(self.value()?);
+                if !self.eat(',') {
+                    self.expect(')')?;
+                    break;
+                }
+            }
+            Ok(RonValue::Tuple(name, items))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn integer(value: f64) -> RonValue {
+        RonValue::Number {
+            value,
+            integer: true,
+        }
+    }
+
+    fn float(value: f64) -> RonValue {
+        RonValue::Number {
+            value,
+            integer: false,
+        }
+    }
+
+    fn parse(input: &str) -> RonValue {
+        parse_ron_value(input).unwrap_or_else(|e| panic!("failed to parse {input}: {e}"))
+    }
+
+    #[test]
+    fn bools_and_identifiers() {
+        assert_eq!(parse("true"), RonValue::Bool(true));
+        assert_eq!(parse(" false "), RonValue::Bool(false));
+        assert_eq!(parse("None"), RonValue::Ident("None".into()));
+        assert_eq!(
+            parse("Unit_Variant2"),
+            RonValue::Ident("Unit_Variant2".into())
+        );
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(parse("42"), integer(42.0));
+        assert_eq!(parse("-7"), integer(-7.0));
+        assert_eq!(parse("+7"), integer(7.0));
+        assert_eq!(parse("1_000_000"), integer(1_000_000.0));
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(parse("1.5"), float(1.5));
+        assert_eq!(parse("-0.25"), float(-0.25));
+        assert_eq!(parse(".5"), float(0.5));
+        assert_eq!(parse("1e3"), float(1000.0));
+        assert_eq!(parse("2.5E-2"), float(0.025));
+        assert_eq!(parse("inf"), float(f64::INFINITY));
+        assert_eq!(parse("-inf"), float(f64::NEG_INFINITY));
+        assert!(
+            matches!(parse("NaN"), RonValue::Number { value, integer: false } if value.is_nan())
+        );
+    }
+
+    #[test]
+    fn strings_and_chars() {
+        assert_eq!(parse("\"hello\""), RonValue::String("hello".into()));
+        assert_eq!(
+            parse("\"a\\n\\t\\\"b\\\\\""),
+            RonValue::String("a\n\t\"b\\".into())
+        );
+        assert_eq!(
+            parse("\"\\u{1F600}\""),
+            RonValue::String("\u{1F600}".into())
+        );
+        assert_eq!(parse("'x'"), RonValue::Char('x'));
+        assert_eq!(parse("'\\''"), RonValue::Char('\''));
+    }
+
+    #[test]
+    fn tuples_and_structs() {
+        assert_eq!(parse("()"), RonValue::Tuple(None, vec![]));
+        assert_eq!(
+            parse("(1, 2.0)"),
+            RonValue::Tuple(None, vec![integer(1.0), float(2.0)])
+        );
+        assert_eq!(
+            parse("Some(3)"),
+            RonValue::Tuple(Some("Some".into()), vec![integer(3.0)])
+        );
+        assert_eq!(
+            parse("Unit()"),
+            RonValue::Tuple(Some("Unit".into()), vec![])
+        );
+        assert_eq!(
+            parse("(x: 1, y: -2,)"),
+            RonValue::Struct(
+                None,
+                vec![("x".into(), integer(1.0)), ("y".into(), integer(-2.0))]
+            )
+        );
+        assert_eq!(
+            parse("Vec3 ( x : 1.0 , y:0.0, z: 0.5 )"),
+            RonValue::Struct(
+                Some("Vec3".into()),
+                vec![
+                    ("x".into(), float(1.0)),
+                    ("y".into(), float(0.0)),
+                    ("z".into(), float(0.5))
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn lists_and_maps() {
+        assert_eq!(parse("[]"), RonValue::List(vec![]));
+        assert_eq!(
+            parse("[1, 2, 3,]"),
+            RonValue::List(vec![integer(1.0), integer(2.0), integer(3.0)])
+        );
+        assert_eq!(parse("{}"), RonValue::Map(vec![]));
+        assert_eq!(
+            parse("{\"a\": 1, 2: [true]}"),
+            RonValue::Map(vec![
+                (RonValue::String("a".into()), integer(1.0)),
+                (integer(2.0), RonValue::List(vec![RonValue::Bool(true)])),
+            ])
+        );
+    }
+
+    #[test]
+    fn nested_values() {
+        let value = parse(
+            "Player(
+                name: \"Bob\",
+                stats: (health: 100, regen: [(0.5, 1.0)]),
+                weapon: Some(Sword(damage: 1.5)),
+                state: Idle,
+                tags: {\"a\": Flag(true)},
+            )",
+        );
+        assert_eq!(
+            value,
+            RonValue::Struct(
+                Some("Player".into()),
+                vec![
+                    ("name".into(), RonValue::String("Bob".into())),
+                    (
+                        "stats".into(),
+                        RonValue::Struct(
+                            None,
+                            vec![
+                                ("health".into(), integer(100.0)),
+                                (
+                                    "regen".into(),
+                                    RonValue::List(vec![RonValue::Tuple(
+                                        None,
+                                        vec![float(0.5), float(1.0)]
+                                    )])
+                                ),
+                            ]
+                        )
+                    ),
+                    (
+                        "weapon".into(),
+                        RonValue::Tuple(
+                            Some("Some".into()),
+                            vec![RonValue::Struct(
+                                Some("Sword".into()),
+                                vec![("damage".into(), float(1.5))]
+                            )]
+                        )
+                    ),
+                    ("state".into(), RonValue::Ident("Idle".into())),
+                    (
+                        "tags".into(),
+                        RonValue::Map(vec![(
+                            RonValue::String("a".into()),
+                            RonValue::Tuple(Some("Flag".into()), vec![RonValue::Bool(true)])
+                        )])
+                    ),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn malformed_input() {
+        for input in [
+            "",
+            "   ",
+            "\"unterminated",
+            "'ab'",
+            "'",
+            "(1, 2",
+            "(x: 1, y 2)",
+            "(x: 1, 2: 3)",
+            "[1 2]",
+            "{\"a\" 1}",
+            "{\"a\": 1",
+            "1 2",
+            "1.2.3",
+            "--1",
+            "@",
+            "\"\\u{110000}\"",
+            // not written by the Blender add-on, see the module documentation
+            "// comment\n1",
+            "r\"raw\"",
+            "b\"bytes\"",
+            "0xFF",
+            "\"\\x41\"",
+        ] {
+            assert!(
+                parse_ron_value(input).is_err(),
+                "{input:?} should not parse"
+            );
+        }
+    }
+}
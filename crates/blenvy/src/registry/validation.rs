@@ -0,0 +1,634 @@
+use std::fmt;
+
+use bevy::{
+    gltf::{GltfExtras, GltfMaterialExtras, GltfMeshExtras, GltfSceneExtras},
+    prelude::*,
+    utils::HashMap,
+};
+use serde_json::{Map, Value};
+
+use crate::{
+    extras_to_component_ron_strings, parse_ron_value, registry_type_defs, type_path_from_ref, BlenvyConfig,
+    ExtrasComponentName, GltfProcessed, RonValue,
+};
+
+/// What is wrong with a component found in gltf extras
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExtrasIssueKind {
+    /// the extras or the component's value are not valid RON
+    UnparsableRon(String),
+    /// there is no type with this name in the registry
+    UnknownComponent,
+    /// the value has a field that the struct (or struct variant) does not have
+    UnknownField(String),
+    /// a required field of the struct (or struct variant) is missing from the value
+    MissingField(String),
+    /// the enum does not have a variant with this name
+    UnknownVariant(String),
+    TypeMismatch { expected: String, found: String },
+    /// wrong number of elements for a tuple, tuple struct or tuple variant
+    WrongLength { expected: usize, found: usize },
+}
+
+/// A single problem found while validating gltf extras against the registry
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtrasIssue {
+    /// path of the node the extras belong to (ie `World/Level1/Chest`)
+    pub node_path: String,
+    /// name of the component as written in the extras
+    pub component: String,
+    /// path to the faulty value inside the component (ie `.stats.max_health` or `[2]`), empty for the component itself
+    pub value_path: String,
+    pub kind: ExtrasIssueKind,
+}
+
+impl fmt::Display for ExtrasIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}{}: ", self.node_path, self.component, self.value_path)?;
+        match &self.kind {
+            ExtrasIssueKind::UnparsableRon(error) => write!(f, "unparsable RON ({error})"),
+            ExtrasIssueKind::UnknownComponent => write!(f, "unknown component"),
+            ExtrasIssueKind::UnknownField(field) => write!(f, "unknown field '{field}'"),
+            ExtrasIssueKind::MissingField(field) => write!(f, "missing field '{field}'"),
+            ExtrasIssueKind::UnknownVariant(variant) => write!(f, "unknown variant '{variant}'"),
+            ExtrasIssueKind::TypeMismatch { expected, found } => {
+                write!(f, "expected {expected}, found {found}")
+            }
+            ExtrasIssueKind::WrongLength { expected, found } => {
+                write!(f, "expected {expected} elements, found {found}")
+            }
+        }
+    }
+}
+
+/// Validates all the components inside of the given gltf extras (RON string) against the registry schema definitions
+/// (ie the `$defs` of the registry export, see [`registry_type_defs`])
+pub fn validate_extras(node_path: &str, extras: &str, defs: &Map<String, Value>) -> Vec<ExtrasIssue> {
+    let issue = |component: &str, value_path: String, kind| ExtrasIssue {
+        node_path: node_path.to_string(),
+        component: component.to_string(),
+        value_path,
+        kind,
+    };
+
+    let components = match extras_to_component_ron_strings(extras) {
+        Ok(components) => components,
+        Err(error) => return vec![issue("", String::new(), ExtrasIssueKind::UnparsableRon(error))],
+    };
+
+    let mut issues = vec![];
+    for (name, ron_string) in components {
+        let Some(type_path) = find_type_path(&name, defs) else {
+            issues.push(issue(name.as_str(), String::new(), ExtrasIssueKind::UnknownComponent));
+            continue;
+        };
+        let value = match parse_ron_value(&ron_string) {
+            Ok(value) => value,
+            // valid RON using syntax not written by the Blender add-on: cannot be checked, but is not an issue either
+            Err(_) if ron::from_str::<ron::Value>(&ron_string).is_ok() => continue,
+            Err(error) => {
+                issues.push(issue(
+                    name.as_str(),
+                    String::new(),
+                    ExtrasIssueKind::UnparsableRon(error),
+                ));
+                continue;
+            }
+        };
+        let mut value_issues = vec![];
+        validate_value(defs, type_path, &value, String::new(), &mut value_issues);
+        issues.extend(
+            value_issues
+                .into_iter()
+                .map(|(value_path, kind)| issue(name.as_str(), value_path, kind)),
+        );
+    }
+    issues
+}
+
+pub(crate) fn find_type_path<'a>(name: &'a ExtrasComponentName, defs: &'a Map<String, Value>) -> Option<&'a str> {
+    match name {
+        ExtrasComponentName::TypePath(path) => defs.contains_key(path).then_some(path.as_str()),
+        ExtrasComponentName::ShortName(short_name) => defs
+            .iter()
+            .find(|(_, def)| def.get("short_name").and_then(Value::as_str) == Some(short_name))
+            .map(|(path, _)| path.as_str()),
+    }
+}
+
+/// returns the type path a schema entry points to
+/// supports both `{"$ref": ...}` and the older `{"type": {"$ref": ...}}`
+pub(crate) fn schema_ref(schema: &Value) -> Option<String> {
+    schema
+        .get("$ref")
+        .or_else(|| schema.get("type").and_then(|t| t.get("$ref")))
+        .and_then(Value::as_str)
+        .and_then(type_path_from_ref)
+}
+
+type ValueIssues = Vec<(String, ExtrasIssueKind)>;
+
+fn mismatch(expected: &str, found: &RonValue) -> ExtrasIssueKind {
+    ExtrasIssueKind::TypeMismatch {
+        expected: expected.to_string(),
+        found: found.kind().to_string(),
+    }
+}
+
+fn validate_value(
+    defs: &Map<String, Value>,
+    type_path: &str,
+    value: &RonValue,
+    value_path: String,
+    issues: &mut ValueIssues,
+) {
+    // types not present in the schema cannot be checked any further
+    let Some(def) = defs.get(type_path) else {
+        return;
+    };
+    let type_info = def.get("typeInfo").and_then(Value::as_str).unwrap_or_default();
+    match type_info {
+        "Struct" => validate_struct(defs, def, value, value_path, issues),
+        "TupleStruct" | "Tuple" => {
+            // newtypes keep their single item next to the inner type's schema
+            let prefix_items = match array(def, "x-blenvy-prefixItems") {
+                [] => array(def, "prefixItems"),
+                prefix_items => prefix_items,
+            };
+            match value {
+                RonValue::Tuple(_, items) => {
+                    validate_items(defs, prefix_items, items, value_path, issues);
+                }
+                // newtypes can be written without the wrapping parenthesis
+                _ if prefix_items.len() == 1 => {
+                    if let Some(item_type) = schema_ref(&prefix_items[0]) {
+                        validate_value(defs, &item_type, value, value_path, issues);
+                    }
+                }
+                _ => issues.push((value_path, mismatch("tuple", value))),
+            }
+        }
+        "List" | "Array" | "Set" => {
+            // serde serializes arrays as tuples
+            let (RonValue::List(items) | RonValue::Tuple(None, items)) = value else {
+                issues.push((value_path, mismatch("list", value)));
+                return;
+            };
+            // older exports point to the container itself instead of the item type: skip those
+            if let Some(item_type) = def
+                .get("items")
+                .and_then(schema_ref)
+                .filter(|item_type| item_type != type_path)
+            {
+                for (index, item) in items.iter().enumerate() {
+                    validate_value(defs, &item_type, item, format!("{value_path}[{index}]"), issues);
+                }
+            }
+        }
+        "Map" => {
+            let RonValue::Map(entries) = value else {
+                issues.push((value_path, mismatch("map", value)));
+                return;
+            };
+            let key_type = def
+                .get("keyType")
+                .and_then(schema_ref)
+                .filter(|key_type| key_type != type_path);
+            let value_type = def
+                .get("valueType")
+                .and_then(schema_ref)
+                .filter(|value_type| value_type != type_path);
+            for (index, (key, entry_value)) in entries.iter().enumerate() {
+                if let Some(key_type) = &key_type {
+                    validate_value(defs, key_type, key, format!("{value_path}{{key {index}}}"), issues);
+                }
+                if let Some(value_type) = &value_type {
+                    validate_value(defs, value_type, entry_value, format!("{value_path}{{{index}}}"), issues);
+                }
+            }
+        }
+        "Enum" => validate_enum(defs, def, value, value_path, issues),
+        "Value" => validate_opaque(def, value, value_path, issues),
+        _ => {}
+    }
+}
+
+fn array<'a>(def: &'a Value, key: &str) -> &'a [Value] {
+    def.get(key)
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+fn validate_items(
+    defs: &Map<String, Value>,
+    prefix_items: &[Value],
+    items: &[RonValue],
+    value_path: String,
+    issues: &mut ValueIssues,
+) {
+    if prefix_items.len() != items.len() {
+        issues.push((
+            value_path,
+            ExtrasIssueKind::WrongLength {
+                expected: prefix_items.len(),
+                found: items.len(),
+            },
+        ));
+        return;
+    }
+    for (index, (item_schema, item)) in prefix_items.iter().zip(items).enumerate() {
+        if let Some(item_type) = schema_ref(item_schema) {
+            validate_value(defs, &item_type, item, format!("{value_path}.{index}"), issues);
+        }
+    }
+}
+
+fn validate_struct(
+    defs: &Map<String, Value>,
+    def: &Value,
+    value: &RonValue,
+    value_path: String,
+    issues: &mut ValueIssues,
+) {
+    let empty = Map::new();
+    let properties = def
+        .get("properties")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+
+    match value {
+        RonValue::Struct(_, fields) => {
+            for (field_name, field_value) in fields {
+                match properties.get(field_name).and_then(schema_ref) {
+                    Some(field_type) => validate_value(
+                        defs,
+                        &field_type,
+                        field_value,
+                        format!("{value_path}.{field_name}"),
+                        issues,
+                    ),
+                    None if !properties.contains_key(field_name) => issues.push((
+                        value_path.clone(),
+                        ExtrasIssueKind::UnknownField(field_name.clone()),
+                    )),
+                    None => {}
+                }
+            }
+            for required in array(def, "required").iter().filter_map(Value::as_str) {
+                if !fields.iter().any(|(field_name, _)| field_name == required) {
+                    issues.push((
+                        value_path.clone(),
+                        ExtrasIssueKind::MissingField(required.to_string()),
+                    ));
+                }
+            }
+        }
+        // unit structs
+        RonValue::Ident(_) | RonValue::Tuple(_, _) if properties.is_empty() => {}
+        // types with custom serde implementations (ie `Vec3` => `(1.0, 2.0, 3.0)`) use positional fields
+        RonValue::Tuple(_, items) if items.len() == properties.len() => {
+            for ((field_name, field_schema), item) in properties.iter().zip(items) {
+                if let Some(field_type) = schema_ref(field_schema) {
+                    let path = format!("{value_path}.{field_name}");
+                    validate_value(defs, &field_type, item, path, issues);
+                }
+            }
+        }
+        _ => issues.push((value_path, mismatch("struct", value))),
+    }
+}
+
+fn validate_enum(
+    defs: &Map<String, Value>,
+    def: &Value,
+    value: &RonValue,
+    value_path: String,
+    issues: &mut ValueIssues,
+) {
+    // the variants as described for the Blender add-on, `oneOf` & `enum` for older exports & simple enums
+    let variants = match (array(def, "x-blenvy-variants"), array(def, "enum")) {
+        ([], []) => array(def, "oneOf"),
+        ([], simple_variants) => simple_variants,
+        (variants, _) => variants,
+    };
+    let (variant_name, variant_value) = match value {
+        RonValue::Ident(name) => (name, None),
+        RonValue::Tuple(Some(name), _) | RonValue::Struct(Some(name), _) => (name, Some(value)),
+        _ => {
+            issues.push((value_path, mismatch("enum variant", value)));
+            return;
+        }
+    };
+
+    // simple enums only list the names of their (unit) variants
+    let variant_def = variants.iter().find(|variant| match variant {
+        Value::String(name) => name == variant_name,
+        _ => variant.get("long_name").and_then(Value::as_str) == Some(variant_name.as_str()),
+    });
+    let Some(variant_def) = variant_def else {
+        issues.push((value_path, ExtrasIssueKind::UnknownVariant(variant_name.clone())));
+        return;
+    };
+
+    let variant_path = format!("{value_path}::{variant_name}");
+    let variant_type_info = variant_def.get("typeInfo").and_then(Value::as_str);
+    match (variant_type_info, variant_value) {
+        (None, None) => {}
+        (Some("Tuple"), Some(RonValue::Tuple(_, items))) => {
+            validate_items(defs, array(variant_def, "prefixItems"), items, variant_path, issues);
+        }
+        (Some("Struct"), Some(struct_value @ RonValue::Struct(..))) => {
+            validate_struct(defs, variant_def, struct_value, variant_path, issues);
+        }
+        (expected, _) => issues.push((
+            variant_path,
+            mismatch(&expected.unwrap_or("unit").to_lowercase(), value),
+        )),
+    }
+}
+
+fn validate_opaque(def: &Value, value: &RonValue, value_path: String, issues: &mut ValueIssues) {
+    let expected = def.get("type").and_then(Value::as_str).unwrap_or_default();
+    let (min, max) = (
+        def.get("minimum").and_then(Value::as_f64),
+        def.get("maximum").and_then(Value::as_f64),
+    );
+    let in_range = |number: f64| {
+        min.is_none_or(|min| number >= min) && max.is_none_or(|max| number <= max)
+    };
+    let valid = match expected {
+        "boolean" => matches!(value, RonValue::Bool(_)),
+        "uint" => matches!(value, RonValue::Number { value, integer: true } if *value >= 0.0),
+        "int" => matches!(value, RonValue::Number { integer: true, .. }),
+        "integer" => matches!(value, RonValue::Number { value, integer: true } if in_range(*value)),
+        "float" | "number" => matches!(value, RonValue::Number { .. }),
+        "string" => matches!(value, RonValue::String(_) | RonValue::Char(_)),
+        // anything else has a custom serialization that we cannot check
+        _ => true,
+    };
+    if !valid {
+        let expected = match (min, max) {
+            (Some(min), Some(max)) if expected == "integer" => format!("integer in {min}..={max}"),
+            _ => expected.to_string(),
+        };
+        issues.push((value_path, mismatch(&expected, value)));
+    }
+}
+
+/// Issues found in gltf extras at runtime, see [`validate_gltf_extras`]
+#[derive(Resource, Debug, Default)]
+pub struct ExtrasValidationReport {
+    /// keyed by the path of the node they were found on: validating a node again (ie when its gltf file gets
+    /// reloaded) replaces its previous issues
+    pub issues: HashMap<String, Vec<ExtrasIssue>>,
+}
+
+impl ExtrasValidationReport {
+    pub fn iter(&self) -> impl Iterator<Item = &ExtrasIssue> {
+        self.issues.values().flatten()
+    }
+}
+
+/// builds a `/` separated path of names from the root of the hierarchy down to the given entity
+pub(crate) fn entity_path(entity: Entity, names: &Query<&Name>, parents: &Query<&Parent>) -> String {
+    let mut path: Vec<String> = std::iter::once(entity)
+        .chain(parents.iter_ancestors(entity))
+        .map(|entity| {
+            names
+                .get(entity)
+                .map(|name| name.to_string())
+                .unwrap_or_else(|_| entity.to_string())
+        })
+        .collect();
+    path.reverse();
+    path.join("/")
+}
+
+pub(crate) fn validate_extras_enabled(blenvy_config: Option<Res<BlenvyConfig>>) -> bool {
+    blenvy_config.is_some_and(|config| config.validate_extras)
+}
+
+/// checks the components of all newly added gltf extras against the `AppTypeRegistry`
+/// and stores (& logs) any issues in the [`ExtrasValidationReport`]
+#[allow(clippy::too_many_arguments)]
+pub fn validate_gltf_extras(
+    extras: Query<(Entity, &GltfExtras), (Added<GltfExtras>, Without<GltfProcessed>)>,
+    scene_extras: Query<(Entity, &GltfSceneExtras), (Added<GltfSceneExtras>, Without<GltfProcessed>)>,
+    mesh_extras: Query<(Entity, &GltfMeshExtras), (Added<GltfMeshExtras>, Without<GltfProcessed>)>,
+    material_extras: Query<(Entity, &GltfMaterialExtras), (Added<GltfMaterialExtras>, Without<GltfProcessed>)>,
+    names: Query<&Name>,
+    parents: Query<&Parent>,
+    type_registry: Res<AppTypeRegistry>,
+    mut defs: Local<Option<(usize, Map<String, Value>)>>,
+    mut report: ResMut<ExtrasValidationReport>,
+) {
+    let all_extras: Vec<(Entity, &str)> = extras
+        .iter()
+        .map(|(entity, extra)| (entity, extra.value.as_str()))
+        .chain(scene_extras.iter().map(|(entity, extra)| (entity, extra.value.as_str())))
+        .chain(mesh_extras.iter().map(|(entity, extra)| (entity, extra.value.as_str())))
+        .chain(material_extras.iter().map(|(entity, extra)| (entity, extra.value.as_str())))
+        .collect();
+    if all_extras.is_empty() {
+        return;
+    }
+
+    // only regenerate the schema if types got registered in the meantime
+    let type_registry = type_registry.read();
+    let type_count = type_registry.iter().count();
+    if defs.as_ref().map(|(count, _)| *count) != Some(type_count) {
+        *defs = Some((type_count, registry_type_defs(&type_registry)));
+    }
+    let Some((_, defs)) = defs.as_ref() else {
+        return;
+    };
+
+    // a node can have several kinds of extras
+    let mut node_issues: HashMap<String, Vec<ExtrasIssue>> = HashMap::new();
+    for (entity, extras) in all_extras {
+        let node_path = entity_path(entity, &names, &parents);
+        let issues = validate_extras(&node_path, extras, defs);
+        node_issues.entry(node_path).or_default().extend(issues);
+    }
+    for (node_path, issues) in node_issues {
+        for issue in issues.iter() {
+            warn!("Invalid gltf extras: {}", issue);
+        }
+        if issues.is_empty() {
+            report.issues.remove(&node_path);
+        } else {
+            report.issues.insert(node_path, issues);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn defs() -> Map<String, Value> {
+        let defs = json!({
+            "f32": {"typeInfo": "Value", "type": "float"},
+            "u8": {"typeInfo": "Value", "type": "integer", "minimum": 0, "maximum": 255},
+            "game::Health": {
+                "short_name": "Health",
+                "typeInfo": "Struct",
+                "properties": {"value": {"$ref": "#/$defs/f32"}, "max": {"$ref": "#/$defs/f32"}},
+                "required": ["value"],
+            },
+            "game::Level": {
+                "short_name": "Level",
+                "typeInfo": "TupleStruct",
+                "prefixItems": [{"$ref": "#/$defs/u8"}],
+            },
+            "game::State": {
+                "short_name": "State",
+                "typeInfo": "Enum",
+                "x-blenvy-variants": [
+                    {"long_name": "Idle"},
+                    {"long_name": "Walk", "typeInfo": "Tuple", "prefixItems": [{"$ref": "#/$defs/f32"}]},
+                ],
+            },
+        });
+        let Value::Object(defs) = defs else { unreachable!() };
+        defs
+    }
+
+    /// the kinds & value paths of the issues found in the given component
+    fn issues(component: &str, value: &str) -> Vec<(String, ExtrasIssueKind)> {
+        let extras = json!({ component: value }).to_string();
+        validate_extras("Root/Node", &extras, &defs())
+            .into_iter()
+            .map(|issue| {
+                assert_eq!(issue.node_path, "Root/Node");
+                assert_eq!(issue.component, component);
+                (issue.value_path, issue.kind)
+            })
+            .collect()
+    }
+
+    fn mismatch(expected: &str, found: &str) -> ExtrasIssueKind {
+        ExtrasIssueKind::TypeMismatch {
+            expected: expected.into(),
+            found: found.into(),
+        }
+    }
+
+    #[test]
+    fn valid_components() {
+        assert_eq!(issues("Health", "(value: 1.0, max: 2)"), vec![]);
+        assert_eq!(issues("Level", "(3)"), vec![]);
+        // newtypes without the wrapping parenthesis
+        assert_eq!(issues("Level", "3"), vec![]);
+        assert_eq!(issues("State", "Idle"), vec![]);
+        assert_eq!(issues("State", "Walk(0.5)"), vec![]);
+
+        let extras = json!({"bevy_components": json!({"game::Health": "(value: 1.0)"}).to_string()}).to_string();
+        assert_eq!(validate_extras("Root", &extras, &defs()), vec![]);
+    }
+
+    #[test]
+    fn unknown_components() {
+        assert_eq!(issues("Mana", "(value: 1.0)"), vec![(String::new(), ExtrasIssueKind::UnknownComponent)]);
+        let extras = json!({"bevy_components": json!({"game::Mana": "()"}).to_string()}).to_string();
+        assert_eq!(
+            validate_extras("Root", &extras, &defs())
+                .into_iter()
+                .map(|issue| (issue.component, issue.kind))
+                .collect::<Vec<_>>(),
+            vec![("game::Mana".to_string(), ExtrasIssueKind::UnknownComponent)]
+        );
+    }
+
+    #[test]
+    fn struct_fields() {
+        assert_eq!(issues("Health", "(value: \"full\")"), vec![(".value".into(), mismatch("float", "string"))]);
+        assert_eq!(
+            issues("Health", "(value: 1.0, regen: 2.0)"),
+            vec![(String::new(), ExtrasIssueKind::UnknownField("regen".into()))]
+        );
+        assert_eq!(
+            issues("Health", "(max: 1.0)"),
+            vec![(String::new(), ExtrasIssueKind::MissingField("value".into()))]
+        );
+        assert_eq!(issues("Health", "[1.0]"), vec![(String::new(), mismatch("struct", "list"))]);
+    }
+
+    #[test]
+    fn enum_variants() {
+        assert_eq!(
+            issues("State", "Run"),
+            vec![(String::new(), ExtrasIssueKind::UnknownVariant("Run".into()))]
+        );
+        assert_eq!(issues("State", "Walk(true)"), vec![("::Walk.0".into(), mismatch("float", "bool"))]);
+        assert_eq!(
+            issues("State", "Walk(1.0, 2.0)"),
+            vec![("::Walk".into(), ExtrasIssueKind::WrongLength { expected: 1, found: 2 })]
+        );
+        assert_eq!(issues("State", "Idle(1.0)"), vec![("::Idle".into(), mismatch("unit", "tuple"))]);
+    }
+
+    #[test]
+    fn opaque_values() {
+        assert_eq!(issues("Level", "(1.5)"), vec![(".0".into(), mismatch("integer in 0..=255", "float"))]);
+        assert_eq!(issues("Level", "(300)"), vec![(".0".into(), mismatch("integer in 0..=255", "integer"))]);
+        assert_eq!(issues("Level", "(-1)"), vec![(".0".into(), mismatch("integer in 0..=255", "integer"))]);
+        assert_eq!(
+            issues("Level", "(1, 2)"),
+            vec![(String::new(), ExtrasIssueKind::WrongLength { expected: 1, found: 2 })]
+        );
+    }
+
+    #[test]
+    fn nested_values() {
+        let mut value_issues = vec![];
+        let value = parse_ron_value("(value: Idle)").unwrap();
+        validate_value(&defs(), "game::Health", &value, ".health".into(), &mut value_issues);
+        assert_eq!(value_issues, vec![(".health.value".into(), mismatch("float", "identifier"))]);
+    }
+
+    #[test]
+    fn unparsable_values() {
+        assert!(matches!(
+            issues("Health", "(value: ").as_slice(),
+            [(path, ExtrasIssueKind::UnparsableRon(_))] if path.is_empty()
+        ));
+        // valid RON that is not written by the Blender add-on is not checked
+        assert_eq!(issues("Level", "(0xFF)"), vec![]);
+        assert_eq!(issues("Health", "(value: 1.0 /* full */)"), vec![]);
+    }
+
+    #[test]
+    fn reloaded_extras_replace_previous_issues() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world.init_resource::<ExtrasValidationReport>();
+        let spawn = |world: &mut World, extras: &str| {
+            world
+                .spawn((
+                    Name::new("Chest"),
+                    GltfExtras {
+                        value: extras.to_string(),
+                    },
+                ))
+                .id()
+        };
+
+        let chest = spawn(&mut world, &json!({"Mana": "()"}).to_string());
+        world.run_system_once(validate_gltf_extras).unwrap();
+        world.run_system_once(validate_gltf_extras).unwrap();
+        let report = world.resource::<ExtrasValidationReport>();
+        assert_eq!(report.iter().count(), 1);
+        assert_eq!(report.issues["Chest"][0].kind, ExtrasIssueKind::UnknownComponent);
+
+        // the gltf file got fixed & reloaded
+        world.despawn(chest);
+        spawn(&mut world, "{}");
+        world.run_system_once(validate_gltf_extras).unwrap();
+        assert_eq!(world.resource::<ExtrasValidationReport>().iter().count(), 0);
+    }
+}
@@ -0,0 +1,29 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, DeriveInput};
+
+/// Registers the type (and all of its field types) in the `AppTypeRegistry` & flags it for the registry export automatically,
+/// without any `register_type` / `register_blenvy_type` call: the type also needs to derive `Reflect`.
+///
+/// The registration gets collected (through the `inventory` crate, on all platforms including wasm) & applied by the `BlueprintsPlugin`,
+/// the `ExportRegistryPlugin` or any call to `register_derived_blenvy_types`
+#[proc_macro_derive(BlenvyComponent)]
+pub fn derive_blenvy_component(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    if !input.generics.params.is_empty() {
+        return syn::Error::new_spanned(
+            &input.generics,
+            "BlenvyComponent can not be derived for generic types, register each instantiation with `register_blenvy_type` instead",
+        )
+        .to_compile_error()
+        .into();
+    }
+    let ident = &input.ident;
+
+    quote! {
+        ::blenvy::__inventory::submit! {
+            ::blenvy::DerivedBlenvyRegistration(::blenvy::register_blenvy_type_in::<#ident>)
+        }
+    }
+    .into()
+}
@@ -2,8 +2,8 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 use blenvy::{
-    BlenvyPlugin, BlueprintAnimationPlayerLink, BlueprintAnimations, BlueprintInfo, GameWorldTag,
-    HideUntilReady, SpawnBlueprint,
+    BlenvyPlugin, BlueprintAnimationControl, BlueprintInfo, GameWorldTag, HideUntilReady,
+    SpawnBlueprint,
 };
 
 mod component_examples;
@@ -54,154 +54,42 @@ fn setup_game(mut commands: Commands) {
 //////////////////////////////////
 
 pub fn animation_control(
-    animated_robots: Query<(&BlueprintAnimationPlayerLink, &BlueprintAnimations), With<Robot>>,
-    animated_foxes: Query<(&BlueprintAnimationPlayerLink, &BlueprintAnimations), With<Fox>>,
-
-    mut animation_players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
-
+    animated_robots: Query<Entity, With<Robot>>,
+    animated_foxes: Query<Entity, With<Fox>>,
+    mut animations: BlueprintAnimationControl,
     keycode: Res<ButtonInput<KeyCode>>,
-    // mut entities_with_animations : Query<(&mut AnimationPlayer, &mut BlueprintAnimations)>,
 ) {
     // robots
     if keycode.just_pressed(KeyCode::KeyB) {
         debug!("scan animation for robots");
-        for (link, animations) in animated_robots.iter() {
-            let (mut animation_player, mut animation_transitions) =
-                animation_players.get_mut(link.0).unwrap();
-            debug!("got some animations");
-            let anim_name = "Scan";
-            animation_transitions
-                .play(
-                    &mut animation_player,
-                    *animations
-                        .named_indices
-                        .get(anim_name)
-                        .expect("animation name should be in the list"),
-                    Duration::from_secs(5),
-                )
-                .repeat();
+        for robot in animated_robots.iter() {
+            match animations.crossfade(robot, "Scan", Duration::from_secs(5)) {
+                Ok(animation) => {
+                    animation.repeat();
+                }
+                Err(error) => warn!("{}", error),
+            }
         }
     }
 
     // foxes
-    if keycode.just_pressed(KeyCode::KeyW) {
-        for (link, animations) in animated_foxes.iter() {
-            let (mut animation_player, mut animation_transitions) =
-                animation_players.get_mut(link.0).unwrap();
-
-            let anim_name = "Walk";
-            animation_transitions
-                .play(
-                    &mut animation_player,
-                    *animations
-                        .named_indices
-                        .get(anim_name)
-                        .expect("animation name should be in the list"),
-                    Duration::from_secs(5),
-                )
-                .repeat();
-        }
-    }
-
-    if keycode.just_pressed(KeyCode::KeyX) {
-        for (link, animations) in animated_foxes.iter() {
-            let (mut animation_player, mut animation_transitions) =
-                animation_players.get_mut(link.0).unwrap();
-
-            let anim_name = "Run";
-            animation_transitions
-                .play(
-                    &mut animation_player,
-                    *animations
-                        .named_indices
-                        .get(anim_name)
-                        .expect("animation name should be in the list"),
-                    Duration::from_secs(5),
-                )
-                .repeat();
-        }
-    }
-
-    if keycode.just_pressed(KeyCode::KeyC) {
-        for (link, animations) in animated_foxes.iter() {
-            let (mut animation_player, mut animation_transitions) =
-                animation_players.get_mut(link.0).unwrap();
-
-            let anim_name = "Survey";
-            animation_transitions
-                .play(
-                    &mut animation_player,
-                    *animations
-                        .named_indices
-                        .get(anim_name)
-                        .expect("animation name should be in the list"),
-                    Duration::from_secs(5),
-                )
-                .repeat();
-        }
-    }
-
-    /* Improveement ideas for the future
-    // a bit more ideal API
-    if keycode.just_pressed(KeyCode::B) {
-        for (animation_player, animations) in  animated_robots.iter() {
-            let anim_name = "Scan";
-            if animations.named_animations.contains_key(anim_name) {
-                let clip = animations.named_animations.get(anim_name).unwrap();
-                animation_player.play_with_transition(clip.clone(), Duration::from_secs(5)).repeat();
+    let fox_animation = if keycode.just_pressed(KeyCode::KeyW) {
+        Some("Walk")
+    } else if keycode.just_pressed(KeyCode::KeyX) {
+        Some("Run")
+    } else if keycode.just_pressed(KeyCode::KeyC) {
+        Some("Survey")
+    } else {
+        None
+    };
+    if let Some(anim_name) = fox_animation {
+        for fox in animated_foxes.iter() {
+            match animations.crossfade(fox, anim_name, Duration::from_secs(5)) {
+                Ok(animation) => {
+                    animation.repeat();
+                }
+                Err(error) => warn!("{}", error),
             }
         }
     }
-
-    // even better API
-    if keycode.just_pressed(KeyCode::B) {
-        for (animation_player, animations) in  animated_robots.iter() {
-            animation_player.play_with_transition("Scan", Duration::from_secs(5)).repeat(); // with a merged animationPlayer + animations storage
-            // alternative, perhaps more realistic, and better seperation of concerns
-            animation_player.play_with_transition(animations, "Scan", Duration::from_secs(5)).repeat();
-
-        }
-    }*/
-
-    /*for (mut anim_player, animations) in entities_with_animations.iter_mut(){
-
-        if keycode.just_pressed(KeyCode::W) {
-            let anim_name = "Walk";
-            if animations.named_animations.contains_key(anim_name) {
-                let clip = animations.named_animations.get(anim_name).unwrap();
-                anim_player.play_with_transition(clip.clone(), Duration::from_secs(5)).repeat();
-            }
-        }
-        if keycode.just_pressed(KeyCode::X) {
-            let anim_name = "Run";
-            if animations.named_animations.contains_key(anim_name) {
-                let clip = animations.named_animations.get(anim_name).unwrap();
-                anim_player.play_with_transition(clip.clone(), Duration::from_secs(5)).repeat();
-            }
-        }
-        if keycode.just_pressed(KeyCode::C) {
-            let anim_name = "Survey";
-            if animations.named_animations.contains_key(anim_name) {
-                let clip = animations.named_animations.get(anim_name).unwrap();
-                anim_player.play_with_transition(clip.clone(), Duration::from_secs(5)).repeat();
-            }
-        }
-
-
-
-        if keycode.just_pressed(KeyCode::S) {
-            let anim_name = "Scan";
-            if animations.named_animations.contains_key(anim_name) {
-                let clip = animations.named_animations.get(anim_name).unwrap();
-                anim_player.play_with_transition(clip.clone(), Duration::from_secs(5)).repeat();
-            }
-        }
-        if keycode.just_pressed(KeyCode::I) {
-            let anim_name = "Idle";
-            if animations.named_animations.contains_key(anim_name) {
-                let clip = animations.named_animations.get(anim_name).unwrap();
-                anim_player.play_with_transition(clip.clone(), Duration::from_secs(5)).repeat();
-            }
-        }
-    }*/
 }
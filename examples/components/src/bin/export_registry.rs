@@ -0,0 +1,74 @@
+//! Regenerates `assets/registry.json` without launching the game (ie from CI or a build step):
+//! `cargo run --bin export_registry -- [output path]`
+use bevy::{prelude::*, scene::ScenePlugin};
+use blenvy::{export_app_registry, registry_export_path, BlenvyRegistrationApp};
+// the types need to come from the library, so that their type paths match the ones of the game
+use blenvy_components_example::{blenvy_plugin, ComponentsExamplesPlugin};
+
+/// the app is never run: it only serves to register the types, through the same plugins as the game
+/// (without the ones that need a window or a gpu)
+fn registry_app(asset_plugin: AssetPlugin) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        asset_plugin,
+        ScenePlugin,
+        TransformPlugin,
+        HierarchyPlugin,
+        // our custom plugins
+        ComponentsExamplesPlugin,
+        blenvy_plugin(),
+    ))
+    .register_derived_blenvy_types();
+    app
+}
+
+fn main() {
+    let app = registry_app(AssetPlugin::default());
+    let output = std::env::args()
+        .nth(1)
+        .map_or_else(|| registry_export_path(app.world()), Into::into);
+    if let Err(error) = export_app_registry(app.world(), &output) {
+        eprintln!("failed to export the registry to {output:?}: {error}");
+        std::process::exit(1);
+    }
+    println!("registry exported to {output:?}");
+}
+
+#[cfg(test)]
+mod tests {
+    use blenvy::export_types;
+
+    use super::*;
+
+    #[test]
+    fn same_registry_as_the_game_export() {
+        let assets = std::env::temp_dir().join(format!("blenvy_export_registry_{}", std::process::id()));
+        std::fs::create_dir_all(&assets).unwrap();
+        let mut app = registry_app(AssetPlugin {
+            file_path: assets.to_string_lossy().into_owned(),
+            ..default()
+        });
+
+        let cli_output = assets.join("cli_registry.json");
+        export_app_registry(app.world(), &cli_output).unwrap();
+        // the export done by the game at startup
+        export_types(app.world_mut());
+        let game_output = registry_export_path(app.world());
+        assert_eq!(game_output, assets.join("registry.json"));
+
+        let cli_registry = std::fs::read_to_string(&cli_output).unwrap();
+        let game_registry = std::fs::read_to_string(&game_output).unwrap();
+        std::fs::remove_dir_all(&assets).unwrap();
+        assert_eq!(cli_registry, game_registry);
+        // blenvy's own types & bevy's are part of it
+        for type_path in [
+            "blenvy::blueprints::spawn_from_blueprints::BlueprintInfo",
+            "blenvy::blueprints::animation_graph::AnimationGraphInfos",
+            "bevy_transform::components::transform::Transform",
+            "bevy_core::name::Name",
+        ] {
+            assert!(cli_registry.contains(&format!("\"{type_path}\"")), "{type_path} is missing");
+        }
+    }
+}
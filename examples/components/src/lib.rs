@@ -0,0 +1,9 @@
+pub mod component_examples;
+pub use component_examples::*;
+
+use blenvy::BlenvyPlugin;
+
+/// the blenvy settings of the game, shared with the `export_registry` binary so that both export the same registry
+pub fn blenvy_plugin() -> BlenvyPlugin {
+    BlenvyPlugin::default()
+}
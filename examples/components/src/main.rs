@@ -1,8 +1,6 @@
 use bevy::prelude::*;
-use blenvy::{BlenvyPlugin, BlueprintInfo, GameWorldTag, HideUntilReady, SpawnBlueprint};
-
-mod component_examples;
-use component_examples::*;
+use blenvy::{BlueprintInfo, GameWorldTag, HideUntilReady, SpawnBlueprint};
+use blenvy_components_example::{blenvy_plugin, ComponentsExamplesPlugin};
 
 fn main() {
     App::new()
@@ -10,7 +8,7 @@ fn main() {
             DefaultPlugins.set(AssetPlugin::default()),
             // our custom plugins
             ComponentsExamplesPlugin, // Showcases different type of components /structs
-            BlenvyPlugin::default(),
+            blenvy_plugin(),
         ))
         .add_systems(Startup, setup_game)
         .run();
@@ -1,4 +1,5 @@
 from bpy_types import PropertyGroup
+from .utils import schema_ref, tuple_items, enum_variants, is_complex_enum
 
 conversion_tables = {
     "bool": lambda value: value,
@@ -44,7 +45,7 @@ def property_group_value_to_custom_property_value(property_group, definition, re
             value = '()'
         else:
             for index, field_name in enumerate(property_group.field_names):
-                item_long_name = definition["properties"][field_name]["type"]["$ref"].replace("#/$defs/", "")
+                item_long_name = schema_ref(definition["properties"][field_name])
                 item_definition = registry.type_infos[item_long_name] if item_long_name in registry.type_infos else None
 
                 value = getattr(property_group, field_name)
@@ -59,7 +60,7 @@ def property_group_value_to_custom_property_value(property_group, definition, re
     elif type_info == "Tuple": 
         values = {}
         for index, field_name in enumerate(property_group.field_names):
-            item_long_name = definition["prefixItems"][index]["type"]["$ref"].replace("#/$defs/", "")
+            item_long_name = schema_ref(tuple_items(definition)[index])
             item_definition = registry.type_infos[item_long_name] if item_long_name in registry.type_infos else None
 
             value = getattr(property_group, field_name)
@@ -76,7 +77,7 @@ def property_group_value_to_custom_property_value(property_group, definition, re
         values = {}
         for index, field_name in enumerate(property_group.field_names):
             #print("toto", index, definition["prefixItems"][index]["type"]["$ref"])
-            item_long_name = definition["prefixItems"][index]["type"]["$ref"].replace("#/$defs/", "")
+            item_long_name = schema_ref(tuple_items(definition)[index])
             item_definition = registry.type_infos[item_long_name] if item_long_name in registry.type_infos else None
 
             value = getattr(property_group, field_name)
@@ -91,10 +92,10 @@ def property_group_value_to_custom_property_value(property_group, definition, re
         value = tuple(e for e in list(values.values()))
     elif type_info == "Enum":
         selected = getattr(property_group, "selection")
-        if type_def == "object":
+        if is_complex_enum(definition):
             selection_index = property_group.field_names.index("variant_"+selected)
             variant_name = property_group.field_names[selection_index]
-            variant_definition = definition["oneOf"][selection_index-1]
+            variant_definition = enum_variants(definition)[selection_index-1]
             if "prefixItems" in variant_definition:
                 value = getattr(property_group, variant_name)
                 is_property_group = isinstance(value, PropertyGroup)
@@ -1,4 +1,5 @@
 from bpy_types import PropertyGroup
+from .utils import schema_ref, tuple_items, enum_variants, is_complex_enum
 import re
 
 def parse_struct_string(string, start_nesting=0):
@@ -106,17 +107,22 @@ def parse_tuplestruct_string(string, start_nesting=0):
     return fields
 
 
+# math types are either written with their field names ("Vec3(x:1.0, y:2.0, z:3.0)") or positionally, as serialized by Bevy ("(1.0,2.0,3.0)")
+def parse_vec_fields(value, typeName, field_names):
+    value = value.replace(typeName,"").replace("(", "").replace(")","")
+    if ":" not in value:
+        return parse_tuplestruct_string(value)
+    parsed = parse_struct_string(value)
+    return [parsed[field_name] for field_name in field_names]
+
 def parse_vec2(value, caster, typeName):
-    parsed = parse_struct_string(value.replace(typeName,"").replace("(", "").replace(")","") )
-    return [caster(parsed['x']), caster(parsed['y'])]
+    return [caster(field) for field in parse_vec_fields(value, typeName, ['x', 'y'])]
 
 def parse_vec3(value, caster, typeName):
-    parsed = parse_struct_string(value.replace(typeName,"").replace("(", "").replace(")","") )
-    return [caster(parsed['x']), caster(parsed['y']), caster(parsed['z'])]
+    return [caster(field) for field in parse_vec_fields(value, typeName, ['x', 'y', 'z'])]
 
 def parse_vec4(value, caster, typeName):
-    parsed = parse_struct_string(value.replace(typeName,"").replace("(", "").replace(")","") )
-    return [caster(parsed['x']), caster(parsed['y']), caster(parsed['z']), caster(parsed['w'])]
+    return [caster(field) for field in parse_vec_fields(value, typeName, ['x', 'y', 'z', 'w'])]
 
 def parse_color_rgba(value, caster, typeName):
     parsed = parse_struct_string(value.replace(typeName,"").replace("(", "").replace(")","") )
@@ -188,7 +194,7 @@ def property_group_value_from_custom_property_value(property_group, definition,
     type_info = definition["typeInfo"] if "typeInfo" in definition else None
     type_def = definition["type"] if "type" in definition else None
     properties = definition["properties"] if "properties" in definition else {}
-    prefixItems = definition["prefixItems"] if "prefixItems" in definition else []
+    prefixItems = tuple_items(definition)
     long_name = definition["long_name"]
 
     #is_value_type = type_def in value_types_defaults or long_name in value_types_defaults
@@ -204,7 +210,7 @@ def property_group_value_from_custom_property_value(property_group, definition,
         if len(property_group.field_names) != 0 :
             custom_property_values = parse_struct_string(value, start_nesting=1 if value.startswith("(") else 0)
             for index, field_name in enumerate(property_group.field_names):
-                item_long_name = definition["properties"][field_name]["type"]["$ref"].replace("#/$defs/", "")
+                item_long_name = schema_ref(definition["properties"][field_name])
                 item_definition = registry.type_infos[item_long_name] if item_long_name in registry.type_infos else None
 
                 custom_prop_value = custom_property_values[field_name]
@@ -230,7 +236,7 @@ def property_group_value_from_custom_property_value(property_group, definition,
         custom_property_values = parse_tuplestruct_string(value, start_nesting=1 if len(nesting) == 1 else 1)
 
         for index, field_name in enumerate(property_group.field_names):
-            item_long_name = definition["prefixItems"][index]["type"]["$ref"].replace("#/$defs/", "")
+            item_long_name = schema_ref(tuple_items(definition)[index])
             item_definition = registry.type_infos[item_long_name] if item_long_name in registry.type_infos else None
             
             custom_property_value = custom_property_values[index]
@@ -246,7 +252,7 @@ def property_group_value_from_custom_property_value(property_group, definition,
     elif type_info == "TupleStruct":
         custom_property_values = parse_tuplestruct_string(value, start_nesting=1 if len(nesting) == 1 else 0)
         for index, field_name in enumerate(property_group.field_names):
-            item_long_name = definition["prefixItems"][index]["type"]["$ref"].replace("#/$defs/", "")
+            item_long_name = schema_ref(tuple_items(definition)[index])
             item_definition = registry.type_infos[item_long_name] if item_long_name in registry.type_infos else None
 
             custom_prop_value = custom_property_values[index]
@@ -262,7 +268,7 @@ def property_group_value_from_custom_property_value(property_group, definition,
 
     elif type_info == "Enum":
         field_names = property_group.field_names
-        if type_def == "object":
+        if is_complex_enum(definition):
             regexp = re.search('(^[^\(]+)(\((.*)\))', value)
             try:
                 chosen_variant_raw = regexp.group(1)
@@ -273,7 +279,7 @@ def property_group_value_from_custom_property_value(property_group, definition,
                 chosen_variant_value = ""
                 chosen_variant_name = "variant_" + chosen_variant_raw 
             selection_index = property_group.field_names.index(chosen_variant_name)
-            variant_definition = definition["oneOf"][selection_index-1]
+            variant_definition = enum_variants(definition)[selection_index-1]
             # first we set WHAT variant is selected
             setattr(property_group, "selection", chosen_variant_raw)
 
@@ -5,7 +5,7 @@ from .process_structs import process_structs
 from .process_tuples import process_tuples
 from .process_unit_struct import process_unit_struct
 from .process_enum import process_enums
-from .utils import create_property_group_name
+from .utils import create_property_group_name, tuple_items
 import bpy
 import logging
 
@@ -42,7 +42,7 @@ def process_component(registry, definition, update, options, nesting_long_names=
         annotations = process_enums.process_enums(registry, definition, update, nesting_long_names, depth + 1, processed_types)
     elif type_info == "TupleStruct" or type_info == "Tuple":
         logging.debug(f"Processing TupleStruct/Tuple type: {long_name}")
-        annotations = process_tuples.process_tuples(registry, definition, definition.get("prefixItems", []), update, nesting_long_names, depth + 1, processed_types)
+        annotations = process_tuples.process_tuples(registry, definition, tuple_items(definition), update, nesting_long_names, depth + 1, processed_types)
     elif type_info == "List":
         annotations = process_list.process_list(registry, definition, update, nesting_long_names, depth + 1, processed_types)
     elif type_info == "Value":
@@ -1,12 +1,12 @@
 from bpy.props import (StringProperty)
 from . import process_component
+from .utils import enum_variants, is_complex_enum
 
 def process_enum(registry, definition, enum_values, update, nesting_long_names, depth=0, processed_types=None):
     blender_property_mapping = registry.blender_property_mapping
     long_name = definition["long_name"]
 
-    type_def = definition["type"] if "type" in definition else None
-    variants = definition["oneOf"]
+    variants = enum_variants(definition)
 
     nesting_long_names = nesting_long_names + [long_name]
 
@@ -15,7 +15,7 @@ def process_enum(registry, definition, enum_values, update, nesting_long_names,
 
     #print("processing enum", long_name)#, definition)
 
-    if type_def == "object":
+    if is_complex_enum(definition):
         labels = []
         additional_annotations = {}
         for variant in variants:
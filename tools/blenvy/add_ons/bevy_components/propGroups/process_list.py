@@ -1,6 +1,6 @@
 # add_ons/bevy_components/components/propGroups/process_list.py
 from bpy.props import (StringProperty, IntProperty, CollectionProperty)
-from .utils import generate_wrapper_propertyGroup
+from .utils import generate_wrapper_propertyGroup, schema_ref
 from . import process_component
 import logging
 import re
@@ -50,7 +50,7 @@ def process_list(registry, definition, items, update, nesting_long_names, depth=
         return {}
 
     # Validate the $ref path
-    if "items" not in definition or "type" not in definition["items"] or "$ref" not in definition["items"]["type"]:
+    if "items" not in definition or schema_ref(definition["items"]) is None:
         logging.error(f"Invalid $ref structure in definition: {definition}")
         return {}
     
@@ -58,7 +58,7 @@ def process_list(registry, definition, items, update, nesting_long_names, depth=
     type_infos = registry.type_infos
 
     long_name = definition["long_name"]
-    ref_name = definition["items"]["type"]["$ref"].replace("#/$defs/", "")
+    ref_name = schema_ref(definition["items"])
     
     # Check for self-referential $ref (Bevy 0.15 issue)
     if ref_name == long_name:
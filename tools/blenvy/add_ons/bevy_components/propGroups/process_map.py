@@ -1,5 +1,5 @@
 from bpy.props import (StringProperty, IntProperty, CollectionProperty, PointerProperty)
-from .utils import generate_wrapper_propertyGroup
+from .utils import generate_wrapper_propertyGroup, schema_ref
 from . import process_component
 
 def process_map(registry, definition, update, nesting_long_names=[]):
@@ -10,8 +10,8 @@ def process_map(registry, definition, update, nesting_long_names=[]):
 
     nesting_long_names = nesting_long_names + [long_name]
 
-    value_ref_name = definition["valueType"]["type"]["$ref"].replace("#/$defs/", "")
-    key_ref_name = definition["keyType"]["type"]["$ref"].replace("#/$defs/", "")
+    value_ref_name = schema_ref(definition["valueType"])
+    key_ref_name = schema_ref(definition["keyType"])
 
     #print("definition", definition)
     __annotations__ = {}
@@ -20,7 +20,7 @@ def process_map(registry, definition, update, nesting_long_names=[]):
         key_definition = type_infos[key_ref_name]
         original_long_name = key_definition["long_name"]
         is_key_value_type = original_long_name in value_types_defaults
-        definition_link = definition["keyType"]["type"]["$ref"]
+        definition_link = f"#/$defs/{key_ref_name}"
 
         #if the content of the list is a unit type, we need to generate a fake wrapper, otherwise we cannot use layout.prop(group, "propertyName") as there is no propertyName !
         if is_key_value_type:
@@ -39,7 +39,7 @@ def process_map(registry, definition, update, nesting_long_names=[]):
         value_definition = type_infos[value_ref_name]
         original_long_name = value_definition["long_name"]
         is_value_value_type = original_long_name in value_types_defaults
-        definition_link = definition["valueType"]["type"]["$ref"]
+        definition_link = f"#/$defs/{value_ref_name}"
 
         #if the content of the list is a unit type, we need to generate a fake wrapper, otherwise we cannot use layout.prop(group, "propertyName") as there is no propertyName !
         if is_value_value_type:
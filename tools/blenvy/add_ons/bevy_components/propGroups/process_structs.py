@@ -1,5 +1,6 @@
 from bpy.props import (StringProperty)
 from . import process_component
+from .utils import schema_ref, property_hints
 import logging
 
 def process_structs(registry, definition, properties, update, nesting_long_names, depth=0, processed_types=None):
@@ -21,13 +22,20 @@ def process_structs(registry, definition, properties, update, nesting_long_names
         return __annotations__
 
     for property_name in properties.keys():
-        ref_name = properties[property_name]["type"]["$ref"].replace("#/$defs/", "")
+        ref_name = schema_ref(properties[property_name])
         
         if ref_name in type_infos:
             original = type_infos[ref_name]
             original_long_name = original["long_name"]
             is_value_type = original_long_name in value_types_defaults
             value = value_types_defaults[original_long_name] if is_value_type else None
+            # use the default value & doc comment exported from the Rust side if there are any
+            exported_default = properties[property_name].get("default", None)
+            if is_value_type and isinstance(exported_default, type(value)):
+                value = exported_default
+            description = properties[property_name].get("description", "")
+            if "x-blenvy-unit" in properties[property_name]:
+                description = f"{description} ({properties[property_name]['x-blenvy-unit']})".strip()
             default_values[property_name] = value
 
             if is_value_type:
@@ -35,7 +43,9 @@ def process_structs(registry, definition, properties, update, nesting_long_names
                     blender_property_def = blender_property_mapping[original_long_name]
                     blender_property = blender_property_def["type"](
                         **blender_property_def["presets"],  # we inject presets first
+                        **property_hints(properties[property_name], blender_property_def["type"]),
                         name=property_name,
+                        description=description,
                         default=value,
                         update=update
                     )
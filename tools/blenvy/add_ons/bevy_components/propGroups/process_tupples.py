@@ -1,5 +1,6 @@
 from bpy.props import (StringProperty)
 from . import process_component
+from .utils import schema_ref
 
 def process_tupples(registry, definition, prefixItems, update, nesting_long_names=[]):
     value_types_defaults = registry.value_types_defaults 
@@ -13,7 +14,7 @@ def process_tupples(registry, definition, prefixItems, update, nesting_long_name
     default_values = []
     prefix_infos = []
     for index, item in enumerate(prefixItems):
-        ref_name = item["type"]["$ref"].replace("#/$defs/", "")
+        ref_name = schema_ref(item)
 
         property_name = str(index)# we cheat a bit, property names are numbers here, as we do not have a real property name
        
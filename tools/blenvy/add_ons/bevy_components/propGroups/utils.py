@@ -3,9 +3,68 @@ def update_calback_helper(definition, update, component_name_override):
     return lambda self, context: update(self, context, definition, component_name_override)
 
 import bpy
-from bpy.props import (StringProperty)
+from urllib.parse import unquote
+from bpy.props import (StringProperty, IntProperty, FloatProperty)
 from bpy_types import PropertyGroup
 
+# returns the long name of the type a schema entry points to
+# supports both {"$ref": ...} and the older {"type": {"$ref": ...}}
+def schema_ref(schema):
+    ref = schema.get("$ref", None)
+    if ref is None and isinstance(schema.get("type", None), dict):
+        ref = schema["type"].get("$ref", None)
+    if ref is None:
+        return None
+    return unquote(ref.replace("#/$defs/", "")).replace("~1", "/").replace("~0", "~")
+
+# the items of tuples & tuple structs: newtypes keep theirs in "x-blenvy-prefixItems"
+def tuple_items(definition):
+    return definition.get("x-blenvy-prefixItems", definition.get("prefixItems", []))
+
+# the variants of an enum: the variant definitions for complex enums, the variant names for simple ones
+def enum_variants(definition):
+    if "x-blenvy-variants" in definition:
+        return definition["x-blenvy-variants"]
+    return definition.get("enum", definition.get("oneOf", []))
+
+def is_complex_enum(definition):
+    return "x-blenvy-variants" in definition or definition.get("type", None) == "object"
+
+blender_units = {
+    "m": "LENGTH",
+    "s": "TIME",
+    "m/s": "VELOCITY",
+    "m/s²": "ACCELERATION",
+    "kg": "MASS",
+    "rad": "ROTATION",
+    "W": "POWER",
+}
+
+# converts the editor hints of a field (see editor_hints.rs on the Rust side) into parameters for its Blender property
+def property_hints(field_schema, property_type):
+    hints = {}
+    if property_type in (IntProperty, FloatProperty):
+        cast = int if property_type == IntProperty else float
+        if "minimum" in field_schema:
+            hints["min"] = cast(field_schema["minimum"])
+        if "maximum" in field_schema:
+            hints["max"] = cast(field_schema["maximum"])
+        elif "exclusiveMaximum" in field_schema:
+            hints["max"] = cast(field_schema["exclusiveMaximum"] - 1) if cast == int else cast(field_schema["exclusiveMaximum"])
+        if "x-blenvy-soft-minimum" in field_schema:
+            hints["soft_min"] = cast(field_schema["x-blenvy-soft-minimum"])
+        if "x-blenvy-soft-maximum" in field_schema:
+            hints["soft_max"] = cast(field_schema["x-blenvy-soft-maximum"])
+        if "x-blenvy-step" in field_schema:
+            # blender uses steps of 1/100 for float properties
+            step = field_schema["x-blenvy-step"] * 100 if property_type == FloatProperty else field_schema["x-blenvy-step"]
+            hints["step"] = max(1, int(step))
+        if property_type == FloatProperty and field_schema.get("x-blenvy-unit", None) in blender_units:
+            hints["unit"] = blender_units[field_schema["x-blenvy-unit"]]
+    if property_type == StringProperty and "x-blenvy-asset-path" in field_schema:
+        hints["subtype"] = "FILE_PATH"
+    return hints
+
 # this helper creates a "fake"/wrapper property group that is NOT a real type in the registry
 # usefull for things like value types in list items etc
 def create_property_group_name(long_name, nesting_long_names):
@@ -30,9 +89,7 @@ def generate_wrapper_propertyGroup(wrapped_type_long_name, item_long_name, defin
         "items": False,
         "prefixItems": [
             {
-                "type": {
-                    "$ref": definition_link
-                }
+                "$ref": definition_link
             }
         ],
         "short_name": wrapper_name, # FIXME !!!
@@ -101,6 +101,8 @@ class ComponentsRegistry(PropertyGroup):
         "float": 0.0,
         "uint": 0,
         "int":0,
+        "number": 0.0,
+        "integer": 0,
 
         # todo : we are re-doing the work of the bevy /rust side here, but it seems more pratical to alway look for the same field name on the blender side for matches
         "bool": True,